@@ -4,15 +4,43 @@ local has_setup = false
 
 -- Global registry to store configured tools
 M._tool_registry = {}
+-- Global registries to store configured prompts and resources
+M._prompt_registry = {}
+M._resource_registry = {}
 
 ---@class SetupOptions
 ---@field custom_tools table<string, CustomTool>|nil Custom tools configuration
+---@field custom_prompts table<string, CustomPrompt>|nil Custom prompts configuration
+---@field custom_resources table<string, CustomResource>|nil Custom resources configuration
 
 ---@class CustomTool
 ---@field description string Tool description
 ---@field parameters JSONSchema|nil JSON Schema specification for tool parameters (follows JSON Schema spec)
+---@field annotations ToolAnnotations|nil Behavior hints that let clients auto-approve safe calls
 ---@field handler function Tool execution handler
 
+---@class ToolAnnotations
+---@field read_only boolean|nil The tool does not change the editor
+---@field destructive boolean|nil The tool may overwrite or delete, rather than only add
+---@field idempotent boolean|nil Repeated calls with the same arguments have no additional effect
+---@field open_world boolean|nil The tool reaches beyond Neovim, e.g. the network
+
+---@class CustomPrompt
+---@field description string Prompt description
+---@field arguments PromptArgument[]|nil Arguments the prompt accepts, passed to the handler as strings
+---@field handler function Returns the prompt text, or a list of { role = "user"|"assistant", text = string }
+
+---@class PromptArgument
+---@field name string Argument name
+---@field description string|nil Argument description
+---@field required boolean|nil Whether the argument is required
+
+---@class CustomResource
+---@field uri string Resource URI, e.g. "project://test-output"
+---@field description string|nil Resource description
+---@field mime_type string|nil MIME type of the content (defaults to "text/plain")
+---@field handler function Returns the resource content as a string
+
 ---@class JSONSchema
 ---@field type string Schema type ("object", "string", "number", "boolean", "array", etc.)
 ---@field properties table<string, JSONSchema>|nil Object properties (for type="object")
@@ -130,12 +158,44 @@ function M.setup(opts)
                     parameters = tool_config.parameters or {
                         type = "object",
                     },
+                    annotations = tool_config.annotations,
                     handler = tool_config.handler,
                 }
             end
         end
     end
 
+    -- Store custom prompts in registry with validation
+    if opts.custom_prompts then
+        for prompt_name, prompt_config in pairs(opts.custom_prompts) do
+            if not prompt_config.description or not prompt_config.handler then
+                vim.notify("Invalid prompt config for: " .. prompt_name, vim.log.levels.ERROR)
+            else
+                M._prompt_registry[prompt_name] = {
+                    description = prompt_config.description,
+                    arguments = prompt_config.arguments or {},
+                    handler = prompt_config.handler,
+                }
+            end
+        end
+    end
+
+    -- Store custom resources in registry with validation, keyed by URI
+    if opts.custom_resources then
+        for resource_name, resource_config in pairs(opts.custom_resources) do
+            if not resource_config.uri or not resource_config.handler then
+                vim.notify("Invalid resource config for: " .. resource_name, vim.log.levels.ERROR)
+            else
+                M._resource_registry[resource_config.uri] = {
+                    name = resource_name,
+                    description = resource_config.description,
+                    mime_type = resource_config.mime_type or "text/plain",
+                    handler = resource_config.handler,
+                }
+            end
+        end
+    end
+
     -- PRESERVE: Existing RPC server setup
     local pipe_path = opts.pipe or generate_pipe_path()
     -- vim.notify("Using pipe path: " .. pipe_path, vim.log.levels.INFO)
@@ -152,6 +212,7 @@ function M.get_registered_tools()
             name = tool_name,
             description = tool_config.description,
             input_schema = tool_config.parameters,
+            annotations = tool_config.annotations,
         }
     end
     if next(tools) == nil then
@@ -179,4 +240,83 @@ function M.execute_tool(tool_name, params)
     end
 end
 
+-- Prompt Discovery API for MCP Server
+function M.get_registered_prompts()
+    local prompts = {}
+    for prompt_name, prompt_config in pairs(M._prompt_registry) do
+        prompts[prompt_name] = {
+            name = prompt_name,
+            description = prompt_config.description,
+            arguments = #prompt_config.arguments > 0 and prompt_config.arguments or nil,
+        }
+    end
+    if next(prompts) == nil then
+        return nil
+    else
+        return prompts
+    end
+end
+
+-- Prompt Rendering API, returns { messages = ... } or { error = ... }
+function M.get_prompt(prompt_name, args)
+    local prompt_config = M._prompt_registry[prompt_name]
+
+    if not prompt_config then
+        return { error = "Prompt '" .. prompt_name .. "' not registered" }
+    end
+
+    local success, result = pcall(prompt_config.handler, args or {})
+    if not success then
+        return { error = "Prompt rendering failed: " .. tostring(result) }
+    end
+
+    if type(result) == "string" then
+        result = { { role = "user", text = result } }
+    end
+    local messages = {}
+    for _, message in ipairs(result or {}) do
+        if type(message) == "string" then
+            message = { role = "user", text = message }
+        end
+        table.insert(messages, { role = message.role or "user", text = message.text or "" })
+    end
+    return { description = prompt_config.description, messages = messages }
+end
+
+-- Resource Discovery API for MCP Server
+function M.get_registered_resources()
+    local resources = {}
+    for uri, resource_config in pairs(M._resource_registry) do
+        resources[uri] = {
+            uri = uri,
+            name = resource_config.name,
+            description = resource_config.description,
+            mime_type = resource_config.mime_type,
+        }
+    end
+    if next(resources) == nil then
+        return nil
+    else
+        return resources
+    end
+end
+
+-- Resource Reading API, returns { text = ... } or { error = ... }
+function M.read_resource(uri)
+    local resource_config = M._resource_registry[uri]
+
+    if not resource_config then
+        return { error = "Resource '" .. uri .. "' not registered" }
+    end
+
+    local success, result = pcall(resource_config.handler)
+    if not success then
+        return { error = "Resource read failed: " .. tostring(result) }
+    end
+    if type(result) ~= "string" then
+        result = vim.json.encode(result)
+    end
+    return { text = result, mime_type = resource_config.mime_type }
+end
+
 return M
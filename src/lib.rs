@@ -4,9 +4,15 @@ mod server;
 #[cfg(test)]
 pub mod test_utils;
 
+pub use neovim::{NeovimClientConfig, NeovimClientConfigOverrides};
 pub use server::{
     NeovimMcpServer,
-    core::{auto_connect_current_project_targets, auto_connect_single_target},
+    confirmation::{ConfirmMethod, ConfirmationPolicy},
+    core::{
+        ShutdownCoordinator, auto_connect_current_project_targets, auto_connect_single_target,
+        cleanup_stale_sockets, spawn_nvim,
+    },
+    logging::mcp_logging_layer,
 };
 
 pub type Result<T> = std::result::Result<T, ServerError>;
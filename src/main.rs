@@ -8,15 +8,21 @@ use hyper_util::{
 };
 use rmcp::{
     ServiceExt,
+    service::ServerInitializeError,
     transport::{
         StreamableHttpServerConfig, StreamableHttpService, stdio,
         streamable_http_server::session::local::LocalSessionManager,
     },
 };
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info, warn};
-use tracing_subscriber::EnvFilter;
+use tracing_subscriber::{EnvFilter, Layer, layer::SubscriberExt, util::SubscriberInitExt};
 
-use nvim_mcp::{NeovimMcpServer, auto_connect_current_project_targets, auto_connect_single_target};
+use nvim_mcp::{
+    ConfirmationPolicy, NeovimClientConfig, NeovimClientConfigOverrides, NeovimMcpServer,
+    auto_connect_current_project_targets, auto_connect_single_target, cleanup_stale_sockets,
+    mcp_logging_layer, spawn_nvim,
+};
 
 static LONG_VERSION: OnceLock<String> = OnceLock::new();
 
@@ -44,6 +50,8 @@ fn long_version() -> &'static str {
 enum ConnectBehavior {
     Manual,
     Auto,
+    Spawn,
+    Embed,
     SpecificTarget(String),
 }
 
@@ -52,6 +60,8 @@ impl std::fmt::Display for ConnectBehavior {
         match self {
             ConnectBehavior::Manual => write!(f, "manual"),
             ConnectBehavior::Auto => write!(f, "auto"),
+            ConnectBehavior::Spawn => write!(f, "spawn"),
+            ConnectBehavior::Embed => write!(f, "embed"),
             ConnectBehavior::SpecificTarget(target) => write!(f, "{}", target),
         }
     }
@@ -64,6 +74,8 @@ impl FromStr for ConnectBehavior {
         match s {
             "manual" => Ok(ConnectBehavior::Manual),
             "auto" => Ok(ConnectBehavior::Auto),
+            "spawn" => Ok(ConnectBehavior::Spawn),
+            "embed" => Ok(ConnectBehavior::Embed),
             target => {
                 // Validate TCP address format
                 if target.parse::<std::net::SocketAddr>().is_ok() {
@@ -79,7 +91,7 @@ impl FromStr for ConnectBehavior {
                 }
 
                 Err(format!(
-                    "Invalid target: '{}'. Must be 'manual', 'auto', TCP address (e.g., '127.0.0.1:6666'), or absolute socket path",
+                    "Invalid target: '{}'. Must be 'manual', 'auto', 'spawn', 'embed', TCP address (e.g., '127.0.0.1:6666'), or absolute socket path",
                     target
                 ))
             }
@@ -106,9 +118,70 @@ struct Cli {
     #[arg(long, default_value = "127.0.0.1")]
     http_host: String,
 
-    /// Connection mode: 'manual', 'auto', or specific target (TCP address/socket path)
+    /// Connection mode: 'manual', 'auto', 'spawn', 'embed', or specific target (TCP address/socket path)
     #[arg(long, default_value = "manual")]
     connect: ConnectBehavior,
+
+    /// Init file for the headless Neovim started with `--connect spawn` or `--connect embed`
+    #[arg(long)]
+    spawn_init_file: Option<String>,
+
+    /// Remove socket files left behind by exited Neovim instances on startup
+    #[arg(long)]
+    cleanup_stale_sockets: bool,
+
+    /// Timeout in milliseconds for LSP operations (default: 3000)
+    #[arg(long)]
+    lsp_timeout_ms: Option<u64>,
+
+    /// Maximum number of lines `read` returns when no end line is given
+    #[arg(long)]
+    read_window_lines: Option<u64>,
+
+    /// Maximum size in bytes of a read-only tool response
+    #[arg(long)]
+    max_response_bytes: Option<usize>,
+
+    /// Maximum number of Neovim notifications kept per connection (default: 100)
+    #[arg(long)]
+    notification_max_stored: Option<usize>,
+
+    /// Seconds Neovim notifications are kept per connection (default: 30)
+    #[arg(long)]
+    notification_expiry_secs: Option<u64>,
+
+    /// Seconds to wait for running tool calls to finish on shutdown
+    #[arg(long, default_value_t = 10)]
+    shutdown_timeout_secs: u64,
+
+    /// Ask the user before running a tool that changes the editor, as
+    /// TOOL[=elicit|nvim] (TOOL may be 'all', repeat or separate with commas)
+    #[arg(long, value_delimiter = ',')]
+    confirm: Vec<String>,
+}
+
+/// Resolves on SIGINT (Ctrl-C) or, on unix, SIGTERM
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {e}");
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
 }
 
 #[tokio::main]
@@ -130,10 +203,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         );
         let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
 
-        tracing_subscriber::fmt()
-            .with_writer(non_blocking)
-            .with_ansi(false)
-            .with_env_filter(env_filter)
+        tracing_subscriber::registry()
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(non_blocking)
+                    .with_ansi(false)
+                    .with_filter(env_filter),
+            )
+            .with(mcp_logging_layer())
             .init();
 
         // Note: _guard is a WorkerGuard which is returned by tracing_appender::non_blocking
@@ -142,17 +219,39 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Some(guard)
     } else {
         // Log to stderr (default behavior)
-        tracing_subscriber::fmt()
-            .with_writer(std::io::stderr)
-            .with_env_filter(env_filter)
+        tracing_subscriber::registry()
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(std::io::stderr)
+                    .with_filter(env_filter),
+            )
+            .with(mcp_logging_layer())
             .init();
 
         None
     };
 
     info!("Starting nvim-mcp Neovim server");
+    if cli.cleanup_stale_sockets {
+        let removed = cleanup_stale_sockets().await;
+        info!("Removed {} stale sockets", removed.len());
+    }
+
     let connect_mode = cli.connect.to_string();
-    let server = NeovimMcpServer::with_connect_mode(Some(connect_mode.clone()));
+    let client_config =
+        NeovimClientConfig::default().with_overrides(&NeovimClientConfigOverrides {
+            lsp_timeout_ms: cli.lsp_timeout_ms,
+            read_window_lines: cli.read_window_lines,
+            max_response_bytes: cli.max_response_bytes,
+            notification_max_stored: cli.notification_max_stored,
+            notification_expiry_secs: cli.notification_expiry_secs,
+        });
+    let confirmation_policy = ConfirmationPolicy::parse(&cli.confirm)?;
+    let server = NeovimMcpServer::with_connect_mode(Some(connect_mode.clone()))
+        .with_client_config(client_config.clone())
+        .with_confirmation_policy(confirmation_policy.clone());
+    let shutdown = server.shutdown_coordinator().clone();
+    let shutdown_timeout = std::time::Duration::from_secs(cli.shutdown_timeout_secs);
 
     // Handle connection mode
     let connection_ids = match cli.connect {
@@ -185,6 +284,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 Err(e) => return Err(format!("Failed to connect to {}: {}", target, e).into()),
             }
         }
+        ConnectBehavior::Spawn | ConnectBehavior::Embed => {
+            let embed = matches!(cli.connect, ConnectBehavior::Embed);
+            let (id, client, pid) = spawn_nvim(
+                &server,
+                None,
+                cli.spawn_init_file.as_deref(),
+                embed,
+                server.client_config.clone(),
+            )
+            .await
+            .map_err(|e| format!("Failed to spawn Neovim: {e}"))?;
+            client.setup_autocmd().await?;
+            server.nvim_clients.insert(id.clone(), client);
+            info!("Spawned headless Neovim (pid {pid}) with ID {id}");
+            vec![id]
+        }
         ConnectBehavior::Manual => {
             info!("Manual connection mode - use get_targets and connect tools");
             vec![]
@@ -206,19 +321,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         info!("Starting HTTP server on {}", addr);
         let mut http_config = StreamableHttpServerConfig::default();
         http_config.stateful_mode = true;
+        let session_shutdown = shutdown.clone();
         let service = TowerToHyperService::new(StreamableHttpService::new(
             move || {
-                Ok(NeovimMcpServer::with_connect_mode(Some(
-                    connect_mode.clone(),
-                )))
+                Ok(
+                    NeovimMcpServer::with_connect_mode(Some(connect_mode.clone()))
+                        .with_client_config(client_config.clone())
+                        .with_confirmation_policy(confirmation_policy.clone())
+                        .with_shutdown_coordinator(session_shutdown.clone()),
+                )
             },
             LocalSessionManager::default().into(),
             http_config,
         ));
         let listener = tokio::net::TcpListener::bind(addr).await?;
+        let signal = shutdown_signal();
+        tokio::pin!(signal);
         loop {
             let io = tokio::select! {
-                _ = tokio::signal::ctrl_c() => break,
+                _ = &mut signal => {
+                    info!("Received shutdown signal");
+                    break;
+                }
                 accept = listener.accept() => {
                     TokioIo::new(accept?.0)
                 }
@@ -236,14 +360,41 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     } else {
         // Default stdio mode
         info!("Starting Neovim server on stdio");
-        let service = server.serve(stdio()).await.inspect_err(|e| {
-            error!("Error starting Neovim server: {}", e);
-        })?;
+        // Answer the calls in flight before closing the transport
+        let cancellation_token = CancellationToken::new();
+        let signal_shutdown = shutdown.clone();
+        let signal_cancellation = cancellation_token.clone();
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            info!("Received shutdown signal");
+            signal_shutdown.drain(shutdown_timeout).await;
+            signal_cancellation.cancel();
+        });
 
-        info!("Neovim server started, waiting for connections...");
-        service.waiting().await?;
+        match server
+            .clone()
+            .serve_with_ct(stdio(), cancellation_token)
+            .await
+        {
+            Ok(service) => {
+                info!("Neovim server started, waiting for connections...");
+                service.waiting().await?;
+            }
+            Err(ServerInitializeError::Cancelled) => {
+                info!("Shut down before the client initialized");
+            }
+            Err(e) => {
+                error!("Error starting Neovim server: {}", e);
+                return Err(e.into());
+            }
+        }
     };
+    // Remove our autocmds and stop Neovim instances spawned by the server
+    shutdown.shutdown(shutdown_timeout).await;
     info!("Server shutdown complete");
 
-    Ok(())
+    // The blocking stdin reader would keep the runtime alive until the next
+    // line of input, so exit once the logs are flushed
+    drop(_guard);
+    std::process::exit(0)
 }
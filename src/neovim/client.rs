@@ -6,6 +6,7 @@ use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use async_trait::async_trait;
 use nvim_rs::{Handler, Neovim, create::tokio as create};
@@ -13,11 +14,12 @@ use rmpv::Value;
 use serde::de::{self, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer};
 use tokio::{
-    io::AsyncWrite,
+    io::{AsyncWrite, WriteHalf},
     net::TcpStream,
     sync::Mutex,
     time::{Duration, timeout},
 };
+use tokio_util::compat::{Compat, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
 use tracing::{debug, info, instrument};
 
 use super::{connection::NeovimConnection, error::NeovimError};
@@ -28,18 +30,34 @@ pub trait NeovimClientTrait: Sync {
     /// Get the target of the Neovim connection
     fn target(&self) -> Option<String>;
 
+    /// Whether the connection was lost because the Neovim instance exited
+    fn is_stale(&self) -> bool;
+
+    /// Get the client configuration
+    fn config(&self) -> &NeovimClientConfig;
+
     /// Disconnect from the current Neovim instance
     async fn disconnect(&mut self) -> Result<String, NeovimError>;
 
     /// Get information about all buffers
     async fn get_buffers(&self) -> Result<Vec<BufferInfo>, NeovimError>;
 
+    /// Get details about the connected Neovim instance
+    async fn get_instance_info(&self) -> Result<InstanceInfo, NeovimError>;
+
+    /// List the project-relative paths that complete `prefix`, directories
+    /// ending with `/`
+    async fn complete_project_path(&self, prefix: &str) -> Result<Vec<String>, NeovimError>;
+
     /// Execute Lua code in Neovim
     async fn execute_lua(&self, code: &str) -> Result<Value, NeovimError>;
 
     /// Set up diagnostics changed autocmd
     async fn setup_autocmd(&self) -> Result<(), NeovimError>;
 
+    /// Remove the autocmds installed by `setup_autocmd`
+    async fn teardown_autocmd(&self) -> Result<(), NeovimError>;
+
     /// Wait for a specific notification with timeout
     async fn wait_for_notification(
         &self,
@@ -47,6 +65,10 @@ pub trait NeovimClientTrait: Sync {
         timeout_ms: u64,
     ) -> Result<Notification, NeovimError>;
 
+    /// Receive the notifications of this connection as they arrive, or
+    /// `None` when not connected
+    fn subscribe_notifications(&self) -> Option<tokio::sync::broadcast::Receiver<Notification>>;
+
     /// Wait for LSP client to be ready and attached
     async fn wait_for_lsp_ready(
         &self,
@@ -70,6 +92,13 @@ pub trait NeovimClientTrait: Sync {
     /// Get LSP clients
     async fn lsp_get_clients(&self) -> Result<Vec<LspClient>, NeovimError>;
 
+    /// Select LSP clients supporting `method`, preferring those attached to `document`
+    async fn lsp_select_clients(
+        &self,
+        method: &str,
+        document: Option<&DocumentIdentifier>,
+    ) -> Result<Vec<String>, NeovimError>;
+
     /// Get LSP code actions
     async fn lsp_get_code_actions(
         &self,
@@ -203,6 +232,10 @@ pub trait NeovimClientTrait: Sync {
         document: DocumentIdentifier,
         text_edits: Vec<TextEdit>,
     ) -> Result<(), NeovimError>;
+    /// Ask the user of the Neovim instance to approve `message`, resolving
+    /// to `false` when rejected or unanswered within `timeout_ms`
+    async fn ask_confirmation(&self, message: &str, timeout_ms: u64) -> Result<bool, NeovimError>;
+
     /// Navigate to a specific position in a document
     async fn navigate(
         &self,
@@ -272,17 +305,61 @@ pub struct Notification {
 }
 
 /// Shared state for notification tracking
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct NotificationTracker {
     notifications: Arc<Mutex<Vec<Notification>>>,
     notify_wakers: Arc<Mutex<HashMap<String, Vec<tokio::sync::oneshot::Sender<Notification>>>>>,
+    live: tokio::sync::broadcast::Sender<Notification>,
+    retention: NotificationRetention,
+}
+
+impl Default for NotificationTracker {
+    fn default() -> Self {
+        Self::with_retention(NotificationRetention::default())
+    }
 }
 
 /// Configuration for notification cleanup
 const MAX_STORED_NOTIFICATIONS: usize = 100;
 const NOTIFICATION_EXPIRY_SECONDS: u64 = 30;
+/// Notifications buffered for each live subscriber before it lags
+const LIVE_NOTIFICATION_CAPACITY: usize = 256;
+
+/// How many notifications are kept, and for how long, for later waiters
+#[derive(Debug, Clone, Copy)]
+pub struct NotificationRetention {
+    pub max_stored: usize,
+    pub expiry: Duration,
+}
+
+impl Default for NotificationRetention {
+    fn default() -> Self {
+        Self {
+            max_stored: MAX_STORED_NOTIFICATIONS,
+            expiry: Duration::from_secs(NOTIFICATION_EXPIRY_SECONDS),
+        }
+    }
+}
+
+/// Upper bound between progress re-checks while waiting for LSP readiness
+const LSP_PROGRESS_POLL_MS: u64 = 500;
 
 impl NotificationTracker {
+    pub fn with_retention(retention: NotificationRetention) -> Self {
+        Self {
+            notifications: Default::default(),
+            notify_wakers: Default::default(),
+            live: tokio::sync::broadcast::channel(LIVE_NOTIFICATION_CAPACITY).0,
+            retention,
+        }
+    }
+
+    /// Receive every notification recorded from now on. The receiver closes
+    /// once the connection and all tracker handles are dropped
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<Notification> {
+        self.live.subscribe()
+    }
+
     /// Clean up expired and excess notifications
     async fn cleanup_notifications(&self) {
         let mut notifications = self.notifications.lock().await;
@@ -291,28 +368,37 @@ impl NotificationTracker {
         let now = std::time::SystemTime::now();
         notifications.retain(|n| {
             now.duration_since(n.timestamp)
-                .map(|d| d.as_secs() < NOTIFICATION_EXPIRY_SECONDS)
+                .map(|d| d < self.retention.expiry)
                 .unwrap_or(false)
         });
 
         // If still too many notifications, keep only the most recent ones
-        if notifications.len() > MAX_STORED_NOTIFICATIONS {
-            let excess = notifications.len() - MAX_STORED_NOTIFICATIONS;
+        if notifications.len() > self.retention.max_stored {
+            let excess = notifications.len() - self.retention.max_stored;
             notifications.drain(0..excess);
         }
     }
 
-    /// Record a notification
-    pub async fn record_notification(&self, name: String, args: Vec<Value>) {
-        let notification = Notification {
-            name: name.clone(),
+    /// Send a notification to the live subscribers and the waiting tasks
+    /// without storing it, for frequent events that would otherwise evict the
+    /// stored ones later waiters look for
+    pub async fn publish_notification(&self, name: String, args: Vec<Value>) {
+        self.dispatch_notification(Notification {
+            name,
             args,
             timestamp: std::time::SystemTime::now(),
-        };
+        })
+        .await;
+    }
+
+    /// Send a notification to the live subscribers and the tasks waiting for it
+    async fn dispatch_notification(&self, notification: Notification) {
+        // Live subscribers only miss notifications when none are listening
+        let _ = self.live.send(notification.clone());
 
         // Notify any waiting tasks for this specific notification name first
         let mut wakers = self.notify_wakers.lock().await;
-        if let Some(waiters) = wakers.get_mut(&name) {
+        if let Some(waiters) = wakers.get_mut(&notification.name) {
             while let Some(waker) = waiters.pop() {
                 let _ = waker.send(notification.clone());
             }
@@ -320,7 +406,16 @@ impl NotificationTracker {
 
         // Clean up wakers with no waiters
         wakers.retain(|_, waiters| !waiters.is_empty());
-        drop(wakers); // Release lock early
+    }
+
+    /// Record a notification
+    pub async fn record_notification(&self, name: String, args: Vec<Value>) {
+        let notification = Notification {
+            name,
+            args,
+            timestamp: std::time::SystemTime::now(),
+        };
+        self.dispatch_notification(notification.clone()).await;
 
         // Always store recent notifications for potential future requests
         // but clean up old/excess ones to prevent memory leaks
@@ -329,7 +424,7 @@ impl NotificationTracker {
             notifications.push(notification);
 
             // Trigger cleanup if we're approaching the limit
-            if notifications.len() > MAX_STORED_NOTIFICATIONS * 3 / 4 {
+            if notifications.len() > self.retention.max_stored * 3 / 4 {
                 drop(notifications); // Release lock before calling cleanup
                 self.cleanup_notifications().await;
             }
@@ -354,7 +449,7 @@ impl NotificationTracker {
                     n.name == notification_name
                         && now
                             .duration_since(n.timestamp)
-                            .map(|d| d.as_secs() < NOTIFICATION_EXPIRY_SECONDS)
+                            .map(|d| d < self.retention.expiry)
                             .unwrap_or(false)
                 })
             {
@@ -387,6 +482,33 @@ impl NotificationTracker {
         }
     }
 
+    /// Wait for the next notification with the given name, ignoring previously recorded ones
+    pub async fn wait_for_next_notification(
+        &self,
+        notification_name: &str,
+        timeout_duration: Duration,
+    ) -> Result<Notification, NeovimError> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        self.notify_wakers
+            .lock()
+            .await
+            .entry(notification_name.to_string())
+            .or_insert_with(Vec::new)
+            .push(tx);
+
+        match timeout(timeout_duration, rx).await {
+            Ok(Ok(notification)) => Ok(notification),
+            Ok(Err(_)) => Err(NeovimError::Api(
+                "Notification channel closed unexpectedly".to_string(),
+            )),
+            Err(_) => Err(NeovimError::Api(format!(
+                "Timeout waiting for notification: {}",
+                notification_name
+            ))),
+        }
+    }
+
     /// Clear all recorded notifications
     pub async fn clear_notifications(&self) {
         let mut notifications = self.notifications.lock().await;
@@ -411,13 +533,16 @@ impl NotificationTracker {
 pub struct NeovimHandler<T> {
     _marker: std::marker::PhantomData<T>,
     notification_tracker: NotificationTracker,
+    /// Target of the connection, telling apart the messages of several instances
+    target: Arc<str>,
 }
 
 impl<T> NeovimHandler<T> {
-    pub fn new() -> Self {
+    pub fn new(target: &str, retention: NotificationRetention) -> Self {
         NeovimHandler {
             _marker: std::marker::PhantomData,
-            notification_tracker: NotificationTracker::default(),
+            notification_tracker: NotificationTracker::with_retention(retention),
+            target: target.into(),
         }
     }
 
@@ -431,6 +556,7 @@ impl<T> Clone for NeovimHandler<T> {
         NeovimHandler {
             _marker: std::marker::PhantomData,
             notification_tracker: self.notification_tracker.clone(),
+            target: self.target.clone(),
         }
     }
 }
@@ -443,7 +569,23 @@ where
     type Writer = T;
 
     async fn handle_notify(&self, name: String, args: Vec<Value>, _neovim: Neovim<T>) {
-        info!("handling notification: {name:?}, {args:?}");
+        debug!("handling notification: {name:?}, {args:?}");
+        match name.as_str() {
+            // Only logged, storing them would evict the notifications waiters need
+            "NVIM_MCP_Message" => {
+                log_neovim_message(&self.target, &args);
+                return;
+            }
+            // Sent on every edit or progress report, and only waited for as
+            // they arrive
+            "NVIM_MCP_BufferChanged" | "NVIM_MCP_LspProgress" => {
+                self.notification_tracker
+                    .publish_notification(name, args)
+                    .await;
+                return;
+            }
+            _ => {}
+        }
         self.notification_tracker
             .record_notification(name, args)
             .await;
@@ -463,7 +605,44 @@ where
     }
 }
 
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+/// Log a `vim.notify` or LSP window message forwarded by `setup_autocmd.lua`,
+/// at the level it was sent with
+fn log_neovim_message(nvim_target: &str, args: &[Value]) {
+    let field = |key: &str| {
+        args.first()
+            .and_then(Value::as_map)
+            .and_then(|map| map.iter().find(|(k, _)| k.as_str() == Some(key)))
+            .map(|(_, value)| value)
+    };
+    let message = field("message").and_then(Value::as_str).unwrap_or_default();
+    let source = match (
+        field("source").and_then(Value::as_str),
+        field("client_name"),
+    ) {
+        (Some("lsp"), Some(client_name)) => format!("lsp:{}", client_name.as_str().unwrap_or("")),
+        (source, _) => source.unwrap_or("notify").to_string(),
+    };
+
+    macro_rules! log_message {
+        ($level:ident) => {
+            tracing::$level!(
+                target: "nvim_mcp::neovim::message",
+                nvim_target,
+                source,
+                "{message}"
+            )
+        };
+    }
+    // vim.log.levels: TRACE, DEBUG, INFO, WARN, ERROR
+    match field("level").and_then(Value::as_u64).unwrap_or(2) {
+        4.. => log_message!(error),
+        3 => log_message!(warn),
+        2 => log_message!(info),
+        _ => log_message!(debug),
+    }
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 pub struct Diagnostic {
     pub message: String,
     pub code: Option<serde_json::Value>,
@@ -478,7 +657,7 @@ pub struct Diagnostic {
     pub user_data: Option<UserData>,
 }
 
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 pub struct UserData {
     pub lsp: LSPDiagnostic,
     #[serde(flatten)]
@@ -494,17 +673,123 @@ pub struct LSPDiagnostic {
     pub source: String,
 }
 
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 pub struct LspClient {
     pub id: u64,
     pub name: String,
+    /// The root directory the server was started in
+    pub root_dir: Option<String>,
+    /// The workspace folders the server was configured with
+    #[serde(default, deserialize_with = "lua_array")]
+    pub workspace_folders: Vec<WorkspaceFolder>,
+    /// Filetypes the client is configured to attach to
+    #[serde(default, deserialize_with = "lua_array")]
+    pub filetypes: Vec<String>,
+    /// Neovim buffer IDs the client is attached to
+    #[serde(default, deserialize_with = "lua_array")]
+    pub attached_buffers: Vec<u64>,
+    /// Position encoding negotiated with the server, e.g. "utf-16"
+    pub offset_encoding: Option<String>,
+    /// Whether the `initialize` handshake has completed
+    #[serde(default)]
+    pub initialized: bool,
+    /// Normalized summary of the server capabilities
+    #[serde(default)]
+    pub capabilities: LspCapabilities,
+    /// Work-done progress tasks (`$/progress`) that have begun but not yet ended
+    #[serde(default, deserialize_with = "lua_array")]
+    pub pending_progress: Vec<LspProgressTask>,
+    /// Whether the server is still reporting progress, e.g. indexing the workspace
+    #[serde(default)]
+    pub indexing: bool,
+}
+
+/// Names of the LSP clients selected for a request
+#[derive(Debug, serde::Deserialize)]
+struct LspClientNames {
+    #[serde(default, deserialize_with = "lua_array")]
+    names: Vec<String>,
+}
+
+/// A workspace folder as defined by the LSP specification
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
+pub struct WorkspaceFolder {
+    /// The associated URI for this workspace folder.
+    pub uri: String,
+    /// The name of the workspace folder.
+    pub name: String,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+/// Which LSP requests a client supports, derived from its `server_capabilities`
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct LspCapabilities {
+    pub hover: bool,
+    pub definition: bool,
+    pub declaration: bool,
+    pub type_definition: bool,
+    pub implementation: bool,
+    pub references: bool,
+    pub document_symbol: bool,
+    pub workspace_symbol: bool,
+    pub code_action: bool,
+    pub code_action_resolve: bool,
+    pub rename: bool,
+    pub prepare_rename: bool,
+    pub formatting: bool,
+    pub range_formatting: bool,
+    pub call_hierarchy: bool,
+    pub type_hierarchy: bool,
+}
+
+/// A running work-done progress task reported by an LSP server
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
+pub struct LspProgressTask {
+    /// The progress token
+    pub token: String,
+    /// The title of the `begin` message, e.g. "Indexing"
+    pub title: Option<String>,
+}
+
+/// Deserialize a Lua list that `vim.json.encode` may emit as `{}` when empty
+fn lua_array<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Object(map) if map.is_empty() => Ok(Vec::new()),
+        serde_json::Value::Null => Ok(Vec::new()),
+        value => serde_json::from_value(value).map_err(de::Error::custom),
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub struct BufferInfo {
     pub id: u64,
     pub name: String,
     pub line_count: u64,
+    pub filetype: String,
+    /// Whether the buffer shows up in the buffer list
+    pub listed: bool,
+    pub loaded: bool,
+    /// Size of the contents in bytes, 0 for buffers that are not loaded
+    pub size: u64,
+}
+
+/// Details about a running Neovim instance
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct InstanceInfo {
+    /// Process ID of the Neovim instance
+    pub pid: u32,
+    /// Neovim version, e.g. "0.11.3"
+    pub nvim_version: String,
+    /// Current working directory
+    pub cwd: String,
+    /// Name of the buffer in the current window, empty for unnamed buffers
+    pub current_buffer: String,
+    /// Whether the nvim-mcp plugin has been loaded
+    pub plugin_loaded: bool,
 }
 
 /// Text documents are identified using a URI.
@@ -572,6 +857,16 @@ where
     deserializer.deserialize_any(StringOrStruct(PhantomData))
 }
 
+/// Like [`string_or_struct`], for optional fields used together with `#[serde(default)]`
+pub fn option_string_or_struct<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + FromStr,
+    <T as FromStr>::Err: Display,
+{
+    string_or_struct(deserializer).map(Some)
+}
+
 /// Universal identifier for text documents supporting multiple reference types
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -598,6 +893,57 @@ macro_rules! impl_fromstr_serde_json {
 
 impl_fromstr_serde_json!(DocumentIdentifier);
 
+/// Locates a symbol in a document without a zero-based position
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+#[serde(untagged)]
+pub enum SymbolLocator {
+    /// Text that occurs exactly once on the given zero-based line
+    TextMatch { line: u64, text_match: String },
+    /// Symbol path such as `Server::connect` or `Server.connect`
+    Path(String),
+}
+
+impl FromStr for SymbolLocator {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim_start().starts_with('{') {
+            serde_json::from_str(s)
+        } else {
+            Ok(Self::Path(s.to_string()))
+        }
+    }
+}
+
+/// Split a symbol name or path into comparable segments.
+///
+/// `impl Trait for Type` blocks count as `Type`, generic arguments are dropped and
+/// receivers such as `(*Server).Connect` become `Server`, `Connect`.
+fn symbol_name_segments(name: &str) -> Vec<String> {
+    let name = match name.strip_prefix("impl ") {
+        Some(rest) => rest.rsplit(" for ").next().unwrap_or(rest),
+        None => name,
+    };
+    let mut cleaned = String::with_capacity(name.len());
+    let mut depth = 0usize;
+    for c in name.chars() {
+        match c {
+            '<' | '[' => depth += 1,
+            '>' | ']' => depth = depth.saturating_sub(1),
+            '(' | ')' | '*' | '&' => {}
+            _ if depth == 0 => cleaned.push(c),
+            _ => {}
+        }
+    }
+    cleaned
+        .replace("::", ".")
+        .split(['.', '#'])
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 impl DocumentIdentifier {
     /// Create from buffer ID
     pub fn from_buffer_id(buffer_id: u64) -> Self {
@@ -617,15 +963,19 @@ impl DocumentIdentifier {
 
 /// Position in a text document expressed as zero-based line and zero-based character offset.
 /// A position is between two characters like an 'insert' cursor in an editor.
-#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
+#[derive(
+    Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
 pub struct Position {
     /// Line position in a document (zero-based).
     pub line: u64,
-    /// Character offset on a line in a document (zero-based).
+    /// Character offset on a line in a document (zero-based), counted in Unicode code points.
     pub character: u64,
 }
 
-#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
+#[derive(
+    Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
 pub struct Range {
     /// The range's start position.
     pub start: Position,
@@ -813,6 +1163,97 @@ pub struct WorkspaceEdit {
 
 impl_fromstr_serde_json!(WorkspaceEdit);
 
+/// Longest replacement text shown when describing an edit
+const DESCRIBED_TEXT_MAX_CHARS: usize = 60;
+
+/// Most lines a description of edits is cut to
+const DESCRIPTION_MAX_LINES: usize = 40;
+
+fn truncate_description(mut lines: Vec<String>) -> String {
+    if lines.len() > DESCRIPTION_MAX_LINES {
+        let more = lines.len() - DESCRIPTION_MAX_LINES;
+        lines.truncate(DESCRIPTION_MAX_LINES);
+        lines.push(format!("... and {more} more"));
+    }
+    lines.join("\n")
+}
+
+impl TextEdit {
+    /// One-line description of the edit, with 1-based positions as shown in the editor
+    pub fn describe(&self) -> String {
+        let Range { start, end } = &self.range;
+        let mut text: String = self
+            .new_text
+            .chars()
+            .take(DESCRIBED_TEXT_MAX_CHARS)
+            .collect();
+        if self.new_text.chars().count() > DESCRIBED_TEXT_MAX_CHARS {
+            text.push_str("...");
+        }
+        let change = if self.new_text.is_empty() {
+            "delete".to_string()
+        } else if start == end {
+            format!("insert {text:?}")
+        } else {
+            format!("replace with {text:?}")
+        };
+        format!(
+            "{}:{}-{}:{} {change}",
+            start.line + 1,
+            start.character + 1,
+            end.line + 1,
+            end.character + 1
+        )
+    }
+}
+
+/// Describe text edits one per line, see [`TextEdit::describe`]
+pub fn describe_text_edits(edits: &[TextEdit]) -> String {
+    truncate_description(edits.iter().map(TextEdit::describe).collect())
+}
+
+impl WorkspaceEdit {
+    /// Describe the files the edit changes, with a line for each text edit
+    /// or file operation
+    pub fn describe(&self) -> String {
+        let mut lines = Vec::new();
+        let mut changes: Vec<_> = self.changes.iter().flatten().collect();
+        changes.sort_by(|a, b| a.0.cmp(b.0));
+        for (uri, edits) in changes {
+            lines.push(format!("{uri}:"));
+            lines.extend(edits.iter().map(|edit| format!("  {}", edit.describe())));
+        }
+
+        let field = |change: &serde_json::Value, pointer: &str| {
+            change
+                .pointer(pointer)
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        };
+        for change in self.document_changes.iter().flatten() {
+            match change.get("kind").and_then(serde_json::Value::as_str) {
+                Some("create") => lines.push(format!("create {}", field(change, "/uri"))),
+                Some("delete") => lines.push(format!("delete {}", field(change, "/uri"))),
+                Some("rename") => lines.push(format!(
+                    "rename {} to {}",
+                    field(change, "/oldUri"),
+                    field(change, "/newUri")
+                )),
+                _ => {
+                    lines.push(format!("{}:", field(change, "/textDocument/uri")));
+                    let edits: Vec<TextEdit> = change
+                        .get("edits")
+                        .and_then(|edits| serde_json::from_value(edits.clone()).ok())
+                        .unwrap_or_default();
+                    lines.extend(edits.iter().map(|edit| format!("  {}", edit.describe())));
+                }
+            }
+        }
+        truncate_description(lines)
+    }
+}
+
 /// Formatting options for LSP document formatting
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 #[serde(rename_all = "camelCase")]
@@ -950,7 +1391,7 @@ pub struct ReferenceContext {
     pub include_declaration: bool,
 }
 
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 pub struct HoverResult {
     /// The hover's content
     pub contents: HoverContents,
@@ -959,7 +1400,7 @@ pub struct HoverResult {
     pub range: Option<Range>,
 }
 
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 #[serde(untagged)]
 pub enum HoverContents {
     String(MarkedString),
@@ -981,7 +1422,7 @@ pub enum HoverContents {
 /// escaped.
 ///
 /// @deprecated use MarkupContent instead.
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 pub enum MarkedString {
     String(String),
     Markup { lang: String, value: String },
@@ -996,7 +1437,7 @@ pub enum MarkedString {
 ///
 /// *Please Note* that clients might sanitize the return markdown. A client could
 /// decide to remove HTML from the markdown to avoid script execution.
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 pub struct MarkupContent {
     /// The type of the Markup
     pub kind: MarkupKind,
@@ -1009,7 +1450,7 @@ pub struct MarkupContent {
 ///
 /// Please note that `MarkupKinds` must not start with a `$`. This kinds
 /// are reserved for internal usage.
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 pub enum MarkupKind {
     /// Plain text is supported as a content format
     #[serde(rename = "plaintext")]
@@ -1025,7 +1466,7 @@ pub struct CodeActionResult {
     pub result: Vec<CodeAction>,
 }
 
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 pub struct NavigateResult {
     pub success: bool,
     pub buffer_name: String,
@@ -1130,14 +1571,14 @@ impl From<u8> for SymbolTag {
 }
 
 /// Represents a location inside a resource, such as a line inside a text file.
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 pub struct Location {
     pub uri: String,
     pub range: Range,
 }
 
 /// Represents a link between a source and a target location.
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct LocationLink {
     /// Span of the origin of this link.
@@ -1159,7 +1600,7 @@ pub struct LocationLink {
 
 /// The result of a textDocument/definition request.
 /// Can be a single Location, a list of Locations, or a list of LocationLinks.
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 #[serde(untagged)]
 pub enum LocateResult {
     Single(Location),
@@ -1168,7 +1609,7 @@ pub enum LocateResult {
 }
 
 /// Represents information about programming constructs like variables, classes, interfaces etc.
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SymbolInformation {
     /// The name of this symbol.
@@ -1203,7 +1644,7 @@ pub struct SymbolInformation {
 /// Represents programming constructs like variables, classes, interfaces etc. that appear in a document.
 /// Document symbols can be hierarchical and they have two ranges: one that encloses its definition and
 /// one that points to its most interesting range, e.g. the range of an identifier.
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DocumentSymbol {
     /// The name of this symbol. Will be displayed in the user interface and therefore must not be
@@ -1296,7 +1737,7 @@ pub struct CallHierarchyPrepareParams {
 /// Represents an incoming call, e.g. a caller of a method or constructor.
 ///
 /// @since 3.16.0
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CallHierarchyIncomingCall {
     /// The item that makes the call.
@@ -1319,7 +1760,7 @@ pub struct CallHierarchyIncomingCallsParams {
 /// a method from a constructor etc.
 ///
 /// @since 3.16.0
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CallHierarchyOutgoingCall {
     /// The item that is called.
@@ -1404,13 +1845,63 @@ pub struct TypeHierarchySubtypesParams {
 }
 
 /// Result type for document symbols request
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 #[serde(untagged)]
 pub enum DocumentSymbolResult {
     Symbols(Vec<DocumentSymbol>),
     Information(Vec<SymbolInformation>),
 }
 
+impl DocumentSymbolResult {
+    /// Find symbols whose qualified name ends with `path`, returning each
+    /// match's qualified name and the start of its name.
+    pub fn find_symbol(&self, path: &str) -> Vec<(String, Position)> {
+        fn walk(
+            symbols: &[DocumentSymbol],
+            parents: &mut Vec<String>,
+            query: &[String],
+            matches: &mut Vec<(String, Position)>,
+        ) {
+            for symbol in symbols {
+                let own = symbol_name_segments(&symbol.name);
+                let depth = parents.len();
+                parents.extend(own.iter().cloned());
+                if !own.is_empty() && parents.ends_with(query) {
+                    matches.push((parents.join("::"), symbol.selection_range.start.clone()));
+                }
+                if let Some(children) = &symbol.children {
+                    walk(children, parents, query, matches);
+                }
+                parents.truncate(depth);
+            }
+        }
+
+        let query = symbol_name_segments(path);
+        let mut matches = Vec::new();
+        if query.is_empty() {
+            return matches;
+        }
+        match self {
+            Self::Symbols(symbols) => walk(symbols, &mut Vec::new(), &query, &mut matches),
+            Self::Information(symbols) => {
+                for symbol in symbols {
+                    let own = symbol_name_segments(&symbol.name);
+                    let mut qualified = symbol
+                        .container_name
+                        .as_deref()
+                        .map(symbol_name_segments)
+                        .unwrap_or_default();
+                    qualified.extend(own.iter().cloned());
+                    if !own.is_empty() && qualified.ends_with(&query) {
+                        matches.push((qualified.join("::"), symbol.location.range.start.clone()));
+                    }
+                }
+            }
+        }
+        matches
+    }
+}
+
 /// Prepare rename response variants
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
 #[serde(untagged)]
@@ -1468,20 +1959,76 @@ impl ReadDocumentParams {
 }
 
 /// Configuration for Neovim client operations
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
 pub struct NeovimClientConfig {
     /// Timeout in milliseconds for LSP operations (default: 3000ms)
     pub lsp_timeout_ms: u64,
+    /// Maximum number of lines `read` returns when no end line is given (default: unlimited)
+    pub read_window_lines: Option<u64>,
+    /// Maximum size in bytes of a tool response (default: unlimited)
+    pub max_response_bytes: Option<usize>,
+    /// Maximum number of notifications kept for later waiters (default: 100)
+    pub notification_max_stored: usize,
+    /// Seconds a notification is kept for later waiters (default: 30)
+    pub notification_expiry_secs: u64,
 }
 
 impl Default for NeovimClientConfig {
     fn default() -> Self {
         Self {
             lsp_timeout_ms: 3000,
+            read_window_lines: None,
+            max_response_bytes: None,
+            notification_max_stored: MAX_STORED_NOTIFICATIONS,
+            notification_expiry_secs: NOTIFICATION_EXPIRY_SECONDS,
+        }
+    }
+}
+
+impl NeovimClientConfig {
+    /// Apply the overrides that are set on top of this configuration
+    pub fn with_overrides(&self, overrides: &NeovimClientConfigOverrides) -> Self {
+        Self {
+            lsp_timeout_ms: overrides.lsp_timeout_ms.unwrap_or(self.lsp_timeout_ms),
+            read_window_lines: overrides.read_window_lines.or(self.read_window_lines),
+            max_response_bytes: overrides.max_response_bytes.or(self.max_response_bytes),
+            notification_max_stored: overrides
+                .notification_max_stored
+                .unwrap_or(self.notification_max_stored),
+            notification_expiry_secs: overrides
+                .notification_expiry_secs
+                .unwrap_or(self.notification_expiry_secs),
+        }
+    }
+
+    pub fn notification_retention(&self) -> NotificationRetention {
+        NotificationRetention {
+            max_stored: self.notification_max_stored,
+            expiry: Duration::from_secs(self.notification_expiry_secs),
         }
     }
 }
 
+/// Client settings given when connecting, falling back to the server defaults
+#[derive(Debug, Clone, Default, serde::Deserialize, schemars::JsonSchema)]
+pub struct NeovimClientConfigOverrides {
+    /// Timeout in milliseconds for LSP operations (optional)
+    #[serde(default)]
+    pub lsp_timeout_ms: Option<u64>,
+    /// Maximum number of lines `read` returns when no end line is given (optional)
+    #[serde(default)]
+    pub read_window_lines: Option<u64>,
+    /// Maximum size in bytes of a tool response (optional)
+    #[serde(default)]
+    pub max_response_bytes: Option<usize>,
+    /// Maximum number of notifications kept for later waiters (optional)
+    #[serde(default)]
+    pub notification_max_stored: Option<usize>,
+    /// Seconds a notification is kept for later waiters (optional)
+    #[serde(default)]
+    pub notification_expiry_secs: Option<u64>,
+}
+
 pub struct NeovimClient<T>
 where
     T: AsyncWrite + Send + 'static,
@@ -1489,6 +2036,8 @@ where
     connection: Option<NeovimConnection<T>>,
     notification_tracker: Option<NotificationTracker>,
     config: NeovimClientConfig,
+    /// Neovim process started by this client, killed on disconnect
+    process: Option<tokio::process::Child>,
 }
 
 impl<T> Default for NeovimClient<T>
@@ -1500,6 +2049,7 @@ where
             connection: None,
             notification_tracker: None,
             config: NeovimClientConfig::default(),
+            process: None,
         }
     }
 }
@@ -1509,6 +2059,12 @@ type Connection = tokio::net::UnixStream;
 #[cfg(windows)]
 type Connection = tokio::net::windows::named_pipe::NamedPipeClient;
 
+/// Stdout and stdin of an embedded Neovim child process
+pub type Embedded = tokio::io::Join<tokio::process::ChildStdout, tokio::process::ChildStdin>;
+
+/// Target prefix of connections to embedded Neovim instances
+pub const EMBED_TARGET_PREFIX: &str = "embed:";
+
 /// Creates a TextDocumentIdentifier from a file path
 /// This utility function works independently of Neovim buffers
 #[allow(dead_code)]
@@ -1553,6 +2109,104 @@ impl<T> From<NvimExecuteLuaResult<T>> for Result<T, NeovimError> {
     }
 }
 
+/// Include an LSP script prefixed with the `lsp_client.lua` helpers it uses
+macro_rules! lsp_script {
+    ($file:literal) => {
+        concat!(include_str!("lua/lsp_client.lua"), include_str!($file))
+    };
+}
+
+/// Source of the IDs that answers to confirmation prompts are matched by
+static CONFIRMATION_IDS: AtomicU64 = AtomicU64::new(1);
+
+/// Source of the keys under which LSP requests can be cancelled in Neovim
+static LSP_REQUEST_KEYS: AtomicU64 = AtomicU64::new(1);
+
+/// An LSP request that is cancelled in Neovim, sending `$/cancelRequest` to the
+/// language server, when dropped before its response arrived
+struct CancellableLspRequest<T>
+where
+    T: AsyncWrite + Send + 'static,
+{
+    nvim: Neovim<Compat<WriteHalf<T>>>,
+    key: String,
+    done: bool,
+}
+
+impl<T> CancellableLspRequest<T>
+where
+    T: AsyncWrite + Send + 'static,
+{
+    fn new(conn: &NeovimConnection<T>) -> Self {
+        Self {
+            nvim: conn.nvim.clone(),
+            // Other servers connected to the instance register their requests
+            // in the same table, so make the key unique across processes
+            key: format!(
+                "{}-{}",
+                std::process::id(),
+                LSP_REQUEST_KEYS.fetch_add(1, Ordering::Relaxed)
+            ),
+            done: false,
+        }
+    }
+
+    /// Key the script registers the request under, passed as its last argument
+    fn key(&self) -> Value {
+        Value::from(self.key.as_str())
+    }
+
+    /// Wait for the script running the request
+    async fn run<F: Future>(mut self, script: F) -> F::Output {
+        let output = script.await;
+        self.done = true;
+        output
+    }
+}
+
+impl<T> Drop for CancellableLspRequest<T>
+where
+    T: AsyncWrite + Send + 'static,
+{
+    fn drop(&mut self) {
+        if self.done {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        debug!("Cancelling LSP request {}", self.key);
+        let (nvim, key) = (self.nvim.clone(), std::mem::take(&mut self.key));
+        runtime.spawn(async move {
+            let cancel = "local cancel = (_G.NVIM_MCP_LSP_REQUESTS or {})[...]\n\
+                          if cancel then cancel() end";
+            if let Err(e) = nvim
+                .execute_lua(cancel, vec![Value::from(key.as_str())])
+                .await
+            {
+                debug!("Failed to cancel LSP request {}: {}", key, e);
+            }
+        });
+    }
+}
+
+/// Describe the Neovim instance listening on `path` with a single request over
+/// a bare RPC connection, without setting up a client
+#[instrument]
+pub async fn query_instance_info(path: &str) -> Result<InstanceInfo, NeovimError> {
+    let (nvim, io_handler) = create::new_path(path, nvim_rs::rpc::handler::Dummy::new())
+        .await
+        .map_err(|e| NeovimError::Connection(format!("Connection failed: {e}")))?;
+    let io_handler = tokio::spawn(io_handler);
+    let result = nvim
+        .execute_lua(include_str!("lua/get_instance_info.lua"), vec![])
+        .await;
+    io_handler.abort();
+    let info = result.map_err(|e| NeovimError::Api(format!("Failed to get instance info: {e}")))?;
+    serde_json::from_str(info.as_str().unwrap_or_default())
+        .map_err(|e| NeovimError::Api(format!("Failed to parse instance info: {e}")))
+}
+
 impl NeovimClient<Connection> {
     #[instrument(skip(self))]
     pub async fn connect_path(&mut self, path: &str) -> Result<(), NeovimError> {
@@ -1564,7 +2218,7 @@ impl NeovimClient<Connection> {
         }
 
         debug!("Attempting to connect to Neovim at {}", path);
-        let handler = NeovimHandler::new();
+        let handler = NeovimHandler::new(path, self.config.notification_retention());
         let notification_tracker = handler.notification_tracker();
         match create::new_path(path, handler).await {
             Ok((nvim, io_handler)) => {
@@ -1590,6 +2244,135 @@ impl NeovimClient<Connection> {
     }
 }
 
+impl NeovimClient<Connection> {
+    /// Start a headless Neovim instance listening on `listen` and connect to it.
+    /// The process is killed when the client disconnects or is dropped.
+    #[instrument(skip(self))]
+    pub async fn spawn_headless(
+        &mut self,
+        listen: &str,
+        cwd: &Path,
+        init_file: Option<&str>,
+    ) -> Result<u32, NeovimError> {
+        if let Some(conn) = &self.connection {
+            return Err(NeovimError::Connection(format!(
+                "Already connected to {}. Disconnect first.",
+                conn.target()
+            )));
+        }
+
+        debug!("Spawning headless Neovim listening on {}", listen);
+        let mut command = tokio::process::Command::new("nvim");
+        command
+            .args(["--headless", "-n", "-i", "NONE", "--listen", listen])
+            .current_dir(cwd)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .kill_on_drop(true);
+        if let Some(init_file) = init_file {
+            command.args(["-u", init_file]);
+        }
+        let mut child = command.spawn().map_err(|e| {
+            NeovimError::Connection(format!(
+                "Failed to start Neovim, ensure nvim is installed and in PATH: {e}"
+            ))
+        })?;
+        let pid = child.id().unwrap_or_default();
+
+        // Wait for Neovim to start listening
+        let start = std::time::Instant::now();
+        loop {
+            tokio::time::sleep(SPAWN_POLL_INTERVAL).await;
+            if let Ok(Some(status)) = child.try_wait() {
+                return Err(NeovimError::Connection(format!(
+                    "Neovim exited during startup with {status}"
+                )));
+            }
+            if self.connect_path(listen).await.is_ok() {
+                break;
+            }
+            if start.elapsed() >= SPAWN_TIMEOUT {
+                let _ = child.kill().await;
+                return Err(NeovimError::Connection(format!(
+                    "Neovim failed to start within {} seconds at {listen}",
+                    SPAWN_TIMEOUT.as_secs()
+                )));
+            }
+        }
+
+        self.process = Some(child);
+        info!("Spawned headless Neovim (pid {pid}) at {listen}");
+        Ok(pid)
+    }
+}
+
+impl NeovimClient<Embedded> {
+    /// Start Neovim with `--embed` and talk msgpack-rpc over its stdin/stdout,
+    /// without creating a socket. The process is killed when the client
+    /// disconnects or is dropped, and the connection cannot be reestablished.
+    #[instrument(skip(self))]
+    pub async fn connect_embed(
+        &mut self,
+        cwd: &Path,
+        init_file: Option<&str>,
+    ) -> Result<u32, NeovimError> {
+        if let Some(conn) = &self.connection {
+            return Err(NeovimError::Connection(format!(
+                "Already connected to {}. Disconnect first.",
+                conn.target()
+            )));
+        }
+
+        debug!("Starting embedded Neovim in {}", cwd.display());
+        let mut command = tokio::process::Command::new("nvim");
+        command
+            .args(["--embed", "--headless", "-n", "-i", "NONE"])
+            .current_dir(cwd)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .kill_on_drop(true);
+        if let Some(init_file) = init_file {
+            command.args(["-u", init_file]);
+        }
+        let mut child = command.spawn().map_err(|e| {
+            NeovimError::Connection(format!(
+                "Failed to start Neovim, ensure nvim is installed and in PATH: {e}"
+            ))
+        })?;
+        let pid = child.id().unwrap_or_default();
+        let (Some(stdout), Some(stdin)) = (child.stdout.take(), child.stdin.take()) else {
+            return Err(NeovimError::Connection(
+                "Failed to open stdio of embedded Neovim".to_string(),
+            ));
+        };
+
+        let target = format!("{EMBED_TARGET_PREFIX}{pid}");
+        let handler = NeovimHandler::new(&target, self.config.notification_retention());
+        let notification_tracker = handler.notification_tracker();
+        let (reader, writer) = tokio::io::split(tokio::io::join(stdout, stdin));
+        let (nvim, io_handler) = Neovim::new(reader.compat(), writer.compat_write(), handler);
+        self.connection = Some(NeovimConnection::new(
+            nvim,
+            tokio::spawn(async move {
+                let rv = io_handler.await;
+                info!("io_handler completed with result: {:?}", rv);
+                Ok(rv)
+            }),
+            target.clone(),
+        ));
+        self.notification_tracker = Some(notification_tracker);
+        self.process = Some(child);
+        info!("Started embedded Neovim (pid {pid}) as {target}");
+        Ok(pid)
+    }
+}
+
+/// How long to wait for a spawned Neovim instance to start listening
+const SPAWN_TIMEOUT: Duration = Duration::from_secs(10);
+const SPAWN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 impl NeovimClient<TcpStream> {
     #[instrument(skip(self))]
     pub async fn connect_tcp(&mut self, address: &str) -> Result<(), NeovimError> {
@@ -1601,7 +2384,7 @@ impl NeovimClient<TcpStream> {
         }
 
         debug!("Attempting to connect to Neovim at {}", address);
-        let handler = NeovimHandler::new();
+        let handler = NeovimHandler::new(address, self.config.notification_retention());
         let notification_tracker = handler.notification_tracker();
         match create::new_tcp(address, handler).await {
             Ok((nvim, io_handler)) => {
@@ -1632,7 +2415,6 @@ where
     T: AsyncWrite + Send + 'static,
 {
     /// Configure the Neovim client with custom settings
-    #[allow(dead_code)]
     pub fn with_config(mut self, config: NeovimClientConfig) -> Self {
         self.config = config;
         self
@@ -1655,7 +2437,7 @@ where
 
         match conn
             .nvim
-            .execute_lua("return vim.json.encode(vim.diagnostic.get(...))", args)
+            .execute_lua(include_str!("lua/get_diagnostics.lua"), args)
             .await
         {
             Ok(diagnostics) => {
@@ -1737,6 +2519,57 @@ where
         }
     }
 
+    /// Wait until the matching LSP clients have no pending work-done progress
+    /// (e.g. rust-analyzer indexing), since queries return empty results meanwhile
+    #[instrument(skip(self))]
+    async fn wait_for_lsp_progress(
+        &self,
+        client_name: Option<&str>,
+        timeout_ms: u64,
+    ) -> Result<(), NeovimError> {
+        let tracker = self.notification_tracker.as_ref().ok_or_else(|| {
+            NeovimError::Connection("Not connected to any Neovim instance".to_string())
+        })?;
+
+        let start = std::time::Instant::now();
+        loop {
+            let pending: Vec<String> = self
+                .lsp_get_clients()
+                .await?
+                .into_iter()
+                .filter(|c| client_name.is_none_or(|name| c.name == name))
+                .filter(|c| c.indexing)
+                .flat_map(|c| {
+                    c.pending_progress
+                        .into_iter()
+                        .map(move |p| format!("{}: {}", c.name, p.title.unwrap_or(p.token)))
+                })
+                .collect();
+
+            if pending.is_empty() {
+                debug!("LSP client readiness confirmed (no pending progress)");
+                return Ok(());
+            }
+
+            let remaining_ms = timeout_ms.saturating_sub(start.elapsed().as_millis() as u64);
+            if remaining_ms == 0 {
+                return Err(NeovimError::Api(format!(
+                    "Timeout waiting for LSP client {:?} to finish: {}",
+                    client_name,
+                    pending.join(", ")
+                )));
+            }
+
+            // Re-check periodically in case the `end` message raced our waker registration
+            let _ = tracker
+                .wait_for_next_notification(
+                    "NVIM_MCP_LspProgress",
+                    Duration::from_millis(remaining_ms.min(LSP_PROGRESS_POLL_MS)),
+                )
+                .await;
+        }
+    }
+
     /// Universal resolver for converting any DocumentIdentifier to TextDocumentIdentifier
     #[instrument(skip(self))]
     async fn resolve_text_document_identifier(
@@ -1771,6 +2604,14 @@ where
         self.connection.as_ref().map(|c| c.target().to_string())
     }
 
+    fn is_stale(&self) -> bool {
+        self.connection.as_ref().is_some_and(|c| !c.is_alive())
+    }
+
+    fn config(&self) -> &NeovimClientConfig {
+        &self.config
+    }
+
     #[instrument(skip(self))]
     async fn disconnect(&mut self) -> Result<String, NeovimError> {
         debug!("Attempting to disconnect from Neovim");
@@ -1784,6 +2625,15 @@ where
                 tracker.clear_notifications().await;
             }
 
+            // Stop the Neovim instance if this client started it
+            if let Some(mut process) = self.process.take() {
+                if let Err(e) = process.kill().await {
+                    debug!("Failed to kill spawned Neovim: {}", e);
+                }
+                #[cfg(unix)]
+                let _ = std::fs::remove_file(&target);
+            }
+
             debug!("Successfully disconnected from Neovim at {}", target);
             Ok(target)
         } else {
@@ -1820,6 +2670,59 @@ where
         }
     }
 
+    #[instrument(skip(self))]
+    async fn complete_project_path(&self, prefix: &str) -> Result<Vec<String>, NeovimError> {
+        let conn = self.connection.as_ref().ok_or_else(|| {
+            NeovimError::Connection("Not connected to any Neovim instance".to_string())
+        })?;
+
+        match conn
+            .nvim
+            .execute_lua(
+                include_str!("lua/complete_project_path.lua"),
+                vec![Value::from(prefix)],
+            )
+            .await
+        {
+            Ok(paths) => serde_json::from_str(paths.as_str().unwrap_or_default()).map_err(|e| {
+                debug!("Failed to parse project paths: {}", e);
+                NeovimError::Api(format!("Failed to parse project paths: {e}"))
+            }),
+            Err(e) => {
+                debug!("Failed to complete project path: {}", e);
+                Err(NeovimError::Api(format!(
+                    "Failed to complete project path: {e}"
+                )))
+            }
+        }
+    }
+
+    #[instrument(skip(self))]
+    async fn get_instance_info(&self) -> Result<InstanceInfo, NeovimError> {
+        debug!("Getting instance information");
+
+        match self
+            .execute_lua(include_str!("lua/get_instance_info.lua"))
+            .await
+        {
+            Ok(info) => {
+                let info: InstanceInfo = serde_json::from_str(info.as_str().unwrap_or_default())
+                    .map_err(|e| {
+                        debug!("Failed to parse instance info: {}", e);
+                        NeovimError::Api(format!("Failed to parse instance info: {e}"))
+                    })?;
+                debug!("Instance info retrieved successfully");
+                Ok(info)
+            }
+            Err(e) => {
+                debug!("Failed to get instance info: {}", e);
+                Err(NeovimError::Api(format!(
+                    "Failed to get instance info: {e}"
+                )))
+            }
+        }
+    }
+
     #[instrument(skip(self))]
     async fn execute_lua(&self, code: &str) -> Result<Value, NeovimError> {
         debug!("Executing Lua code: {}", code);
@@ -1869,6 +2772,21 @@ where
         }
     }
 
+    #[instrument(skip(self))]
+    async fn teardown_autocmd(&self) -> Result<(), NeovimError> {
+        debug!("Removing autocmd");
+
+        let conn = self.connection.as_ref().ok_or_else(|| {
+            NeovimError::Connection("Not connected to any Neovim instance".to_string())
+        })?;
+
+        conn.nvim
+            .exec_lua(include_str!("lua/teardown_autocmd.lua"), vec![])
+            .await
+            .map_err(|e| NeovimError::Api(format!("Failed to remove autocmd: {e}")))?;
+        Ok(())
+    }
+
     #[instrument(skip(self))]
     async fn get_buffer_diagnostics(&self, buffer_id: u64) -> Result<Vec<Diagnostic>, NeovimError> {
         self.get_diagnostics(Some(buffer_id)).await
@@ -1912,6 +2830,58 @@ where
         }
     }
 
+    #[instrument(skip(self))]
+    async fn lsp_select_clients(
+        &self,
+        method: &str,
+        document: Option<&DocumentIdentifier>,
+    ) -> Result<Vec<String>, NeovimError> {
+        let (buffer_id, path) = match document {
+            Some(DocumentIdentifier::BufferId(id)) => (Value::from(*id), Value::Nil),
+            Some(DocumentIdentifier::ProjectRelativePath(rel_path)) => {
+                let project_root = self.get_project_root().await?;
+                let absolute_path = project_root.join(rel_path);
+                (
+                    Value::Nil,
+                    Value::from(absolute_path.to_string_lossy().to_string()),
+                )
+            }
+            Some(DocumentIdentifier::AbsolutePath(abs_path)) => (
+                Value::Nil,
+                Value::from(abs_path.to_string_lossy().to_string()),
+            ),
+            None => (Value::Nil, Value::Nil),
+        };
+
+        let conn = self.connection.as_ref().ok_or_else(|| {
+            NeovimError::Connection("Not connected to any Neovim instance".to_string())
+        })?;
+
+        match conn
+            .nvim
+            .execute_lua(
+                include_str!("lua/lsp_select_clients.lua"),
+                vec![Value::from(method), buffer_id, path],
+            )
+            .await
+        {
+            Ok(result) => {
+                let selected = serde_json::from_str::<LspClientNames>(result.as_str().unwrap())
+                    .map_err(|e| {
+                        NeovimError::Api(format!("Failed to parse selected LSP clients: {e}"))
+                    })?;
+                debug!("Selected LSP clients for {method}: {:?}", selected.names);
+                Ok(selected.names)
+            }
+            Err(e) => {
+                debug!("Failed to select LSP clients: {}", e);
+                Err(NeovimError::Api(format!(
+                    "Failed to select LSP clients: {e}"
+                )))
+            }
+        }
+    }
+
     #[instrument(skip(self))]
     async fn lsp_get_code_actions(
         &self,
@@ -1945,7 +2915,7 @@ where
         match conn
             .nvim
             .execute_lua(
-                include_str!("lua/lsp_client_get_code_actions.lua"),
+                lsp_script!("lua/lsp_client_get_code_actions.lua"),
                 vec![
                     Value::from(client_name), // client_name
                     Value::from(
@@ -2008,7 +2978,7 @@ where
         match conn
             .nvim
             .execute_lua(
-                include_str!("lua/lsp_hover.lua"),
+                lsp_script!("lua/lsp_hover.lua"),
                 vec![
                     Value::from(client_name), // client_name
                     Value::from(
@@ -2065,7 +3035,7 @@ where
         match conn
             .nvim
             .execute_lua(
-                include_str!("lua/lsp_document_symbols.lua"),
+                lsp_script!("lua/lsp_document_symbols.lua"),
                 vec![
                     Value::from(client_name), // client_name
                     Value::from(
@@ -2109,10 +3079,11 @@ where
             NeovimError::Connection("Not connected to any Neovim instance".to_string())
         })?;
 
-        match conn
-            .nvim
-            .execute_lua(
-                include_str!("lua/lsp_workspace_symbols.lua"),
+        let request = CancellableLspRequest::new(conn);
+        let request_key = request.key();
+        match request
+            .run(conn.nvim.execute_lua(
+                lsp_script!("lua/lsp_workspace_symbols.lua"),
                 vec![
                     Value::from(client_name), // client_name
                     Value::from(
@@ -2122,8 +3093,9 @@ where
                         .unwrap(),
                     ), // params
                     Value::from(self.config.lsp_timeout_ms), // timeout_ms
+                    request_key,              // request_key
                 ],
-            )
+            ))
             .await
         {
             Ok(result) => {
@@ -2168,10 +3140,11 @@ where
             _ => 0, // Use buffer 0 as fallback for path-based operations
         };
 
-        match conn
-            .nvim
-            .execute_lua(
-                include_str!("lua/lsp_references.lua"),
+        let request = CancellableLspRequest::new(conn);
+        let request_key = request.key();
+        match request
+            .run(conn.nvim.execute_lua(
+                lsp_script!("lua/lsp_references.lua"),
                 vec![
                     Value::from(client_name), // client_name
                     Value::from(
@@ -2186,8 +3159,9 @@ where
                     ), // params
                     Value::from(self.config.lsp_timeout_ms), // timeout_ms
                     Value::from(buffer_id),   // bufnr
+                    request_key,              // request_key
                 ],
-            )
+            ))
             .await
         {
             Ok(result) => {
@@ -2231,7 +3205,7 @@ where
         match conn
             .nvim
             .execute_lua(
-                include_str!("lua/lsp_definition.lua"),
+                lsp_script!("lua/lsp_definition.lua"),
                 vec![
                     Value::from(client_name), // client_name
                     Value::from(
@@ -2284,7 +3258,7 @@ where
         match conn
             .nvim
             .execute_lua(
-                include_str!("lua/lsp_type_definition.lua"),
+                lsp_script!("lua/lsp_type_definition.lua"),
                 vec![
                     Value::from(client_name), // client_name
                     Value::from(
@@ -2337,7 +3311,7 @@ where
         match conn
             .nvim
             .execute_lua(
-                include_str!("lua/lsp_implementation.lua"),
+                lsp_script!("lua/lsp_implementation.lua"),
                 vec![
                     Value::from(client_name), // client_name
                     Value::from(
@@ -2390,7 +3364,7 @@ where
         match conn
             .nvim
             .execute_lua(
-                include_str!("lua/lsp_declaration.lua"),
+                lsp_script!("lua/lsp_declaration.lua"),
                 vec![
                     Value::from(client_name), // client_name
                     Value::from(
@@ -2440,7 +3414,7 @@ where
         match conn
             .nvim
             .execute_lua(
-                include_str!("lua/lsp_resolve_code_action.lua"),
+                lsp_script!("lua/lsp_resolve_code_action.lua"),
                 vec![
                     Value::from(client_name),
                     Value::from(serde_json::to_string(&code_action).map_err(|e| {
@@ -2487,7 +3461,7 @@ where
         match conn
             .nvim
             .execute_lua(
-                include_str!("lua/lsp_apply_workspace_edit.lua"),
+                lsp_script!("lua/lsp_apply_workspace_edit.lua"),
                 vec![
                     Value::from(client_name),
                     Value::from(serde_json::to_string(&workspace_edit).map_err(|e| {
@@ -2538,7 +3512,7 @@ where
         match conn
             .nvim
             .execute_lua(
-                include_str!("lua/lsp_prepare_rename.lua"),
+                lsp_script!("lua/lsp_prepare_rename.lua"),
                 vec![
                     Value::from(client_name),
                     Value::from(
@@ -2596,7 +3570,7 @@ where
         match conn
             .nvim
             .execute_lua(
-                include_str!("lua/lsp_rename.lua"),
+                lsp_script!("lua/lsp_rename.lua"),
                 vec![
                     Value::from(client_name),
                     Value::from(
@@ -2653,10 +3627,11 @@ where
             options: FormattingOptions,
         }
 
-        match conn
-            .nvim
-            .execute_lua(
-                include_str!("lua/lsp_formatting.lua"),
+        let request = CancellableLspRequest::new(conn);
+        let request_key = request.key();
+        match request
+            .run(conn.nvim.execute_lua(
+                lsp_script!("lua/lsp_formatting.lua"),
                 vec![
                     Value::from(client_name), // client_name
                     Value::from(
@@ -2667,8 +3642,9 @@ where
                         .unwrap(),
                     ),
                     Value::from(self.config.lsp_timeout_ms), // timeout_ms
+                    request_key,                             // request_key
                 ],
-            )
+            ))
             .await
         {
             Ok(result) => {
@@ -2716,10 +3692,11 @@ where
             options: FormattingOptions,
         }
 
-        match conn
-            .nvim
-            .execute_lua(
-                include_str!("lua/lsp_range_formatting.lua"),
+        let request = CancellableLspRequest::new(conn);
+        let request_key = request.key();
+        match request
+            .run(conn.nvim.execute_lua(
+                lsp_script!("lua/lsp_range_formatting.lua"),
                 vec![
                     Value::from(client_name), // client_name
                     Value::from(
@@ -2731,8 +3708,9 @@ where
                         .unwrap(),
                     ),
                     Value::from(self.config.lsp_timeout_ms), // timeout_ms
+                    request_key,                             // request_key
                 ],
-            )
+            ))
             .await
         {
             Ok(result) => {
@@ -2789,7 +3767,7 @@ where
         match conn
             .nvim
             .execute_lua(
-                include_str!("lua/lsp_client_get_code_actions.lua"),
+                lsp_script!("lua/lsp_client_get_code_actions.lua"),
                 vec![
                     Value::from(client_name), // client_name
                     Value::from(
@@ -2838,7 +3816,7 @@ where
         match conn
             .nvim
             .execute_lua(
-                include_str!("lua/lsp_apply_text_edits.lua"),
+                lsp_script!("lua/lsp_apply_text_edits.lua"),
                 vec![
                     Value::from(client_name),
                     Value::from(serde_json::to_string(&text_edits).map_err(|e| {
@@ -2867,6 +3845,86 @@ where
         }
     }
 
+    #[instrument(skip(self))]
+    async fn ask_confirmation(&self, message: &str, timeout_ms: u64) -> Result<bool, NeovimError> {
+        let conn = self.connection.as_ref().ok_or_else(|| {
+            NeovimError::Connection("Not connected to any Neovim instance".to_string())
+        })?;
+        let mut notifications = self.subscribe_notifications().ok_or_else(|| {
+            NeovimError::Connection("Not connected to any Neovim instance".to_string())
+        })?;
+
+        // Every server connected to the instance receives the answer, so make
+        // the ID unique across processes
+        let id = format!(
+            "{}-{}",
+            std::process::id(),
+            CONFIRMATION_IDS.fetch_add(1, Ordering::Relaxed)
+        );
+        conn.nvim
+            .execute_lua(
+                include_str!("lua/ask_confirmation.lua"),
+                vec![Value::from(id.as_str()), Value::from(message)],
+            )
+            .await
+            .map_err(|e| NeovimError::Api(format!("Failed to ask for confirmation: {e}")))?;
+
+        let answer = async {
+            loop {
+                let notification = match notifications.recv().await {
+                    Ok(notification) => notification,
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                        return Err(NeovimError::Connection(
+                            "Connection closed while asking for confirmation".to_string(),
+                        ));
+                    }
+                };
+                if notification.name != "NVIM_MCP_Confirmed" {
+                    continue;
+                }
+                let field = |key: &str| {
+                    notification
+                        .args
+                        .first()
+                        .and_then(Value::as_map)
+                        .and_then(|map| map.iter().find(|(k, _)| k.as_str() == Some(key)))
+                        .map(|(_, value)| value.clone())
+                };
+                if field("id").as_ref().and_then(Value::as_str) == Some(id.as_str()) {
+                    return Ok(field("approved")
+                        .as_ref()
+                        .and_then(Value::as_bool)
+                        .unwrap_or(false));
+                }
+            }
+        };
+        match timeout(Duration::from_millis(timeout_ms), answer).await {
+            Ok(approved) => approved,
+            Err(_) => {
+                debug!("Confirmation {id} was not answered within {timeout_ms}ms");
+                // Invalidate the prompt without waiting, a blocking prompt may
+                // still be open in Neovim
+                let nvim = conn.nvim.clone();
+                tokio::spawn(async move {
+                    let expire = "local confirmations = _G.NVIM_MCP_CONFIRMATIONS or {}
+                                  local id = ...
+                                  if confirmations[id] then
+                                      confirmations[id] = nil
+                                      vim.notify('nvim-mcp: the confirmation request expired, nothing was changed', vim.log.levels.WARN)
+                                  end";
+                    if let Err(e) = nvim
+                        .execute_lua(expire, vec![Value::from(id.as_str())])
+                        .await
+                    {
+                        debug!("Failed to expire confirmation {id}: {e}");
+                    }
+                });
+                Ok(false)
+            }
+        }
+    }
+
     #[instrument(skip(self))]
     async fn navigate(
         &self,
@@ -2933,6 +3991,12 @@ where
             .await
     }
 
+    fn subscribe_notifications(&self) -> Option<tokio::sync::broadcast::Receiver<Notification>> {
+        self.notification_tracker
+            .as_ref()
+            .map(NotificationTracker::subscribe)
+    }
+
     #[instrument(skip(self))]
     async fn wait_for_lsp_ready(
         &self,
@@ -2949,14 +4013,15 @@ where
             Some(expected_name) => clients.iter().any(|c| c.name == expected_name),
         };
 
+        let start = std::time::Instant::now();
+
         // The LSP client might have already attached before we installed the LspAttach autocmd.
         let clients = self.lsp_get_clients().await?;
         if is_ready(&clients) {
-            debug!("LSP client readiness confirmed (already attached)");
-            return Ok(());
+            debug!("LSP client attach confirmed (already attached)");
+            return self.wait_for_lsp_progress(client_name, timeout_ms).await;
         }
 
-        let start = std::time::Instant::now();
         loop {
             let elapsed_ms = start.elapsed().as_millis() as u64;
             let remaining_ms = timeout_ms.saturating_sub(elapsed_ms);
@@ -2992,14 +4057,19 @@ where
                 }
             }
 
-            debug!("LSP client readiness confirmed");
-            return Ok(());
+            debug!("LSP client attach confirmed");
+            return self
+                .wait_for_lsp_progress(
+                    client_name,
+                    timeout_ms.saturating_sub(start.elapsed().as_millis() as u64),
+                )
+                .await;
         }
 
         let clients = self.lsp_get_clients().await?;
         if is_ready(&clients) {
-            debug!("LSP client readiness confirmed (attached during timeout)");
-            Ok(())
+            debug!("LSP client attach confirmed (attached during timeout)");
+            self.wait_for_lsp_progress(client_name, 0).await
         } else {
             Err(NeovimError::Api(format!(
                 "Timeout waiting for LSP client attach: {:?}",
@@ -3061,7 +4131,7 @@ where
         match conn
             .nvim
             .execute_lua(
-                include_str!("lua/lsp_call_hierarchy_prepare.lua"),
+                lsp_script!("lua/lsp_call_hierarchy_prepare.lua"),
                 vec![
                     Value::from(client_name), // client_name
                     Value::from(
@@ -3111,7 +4181,7 @@ where
         match conn
             .nvim
             .execute_lua(
-                include_str!("lua/lsp_call_hierarchy_incoming_calls.lua"),
+                lsp_script!("lua/lsp_call_hierarchy_incoming_calls.lua"),
                 vec![
                     Value::from(client_name), // client_name
                     Value::from(
@@ -3161,7 +4231,7 @@ where
         match conn
             .nvim
             .execute_lua(
-                include_str!("lua/lsp_call_hierarchy_outgoing_calls.lua"),
+                lsp_script!("lua/lsp_call_hierarchy_outgoing_calls.lua"),
                 vec![
                     Value::from(client_name), // client_name
                     Value::from(
@@ -3212,7 +4282,7 @@ where
         match conn
             .nvim
             .execute_lua(
-                include_str!("lua/lsp_type_hierarchy_prepare.lua"),
+                lsp_script!("lua/lsp_type_hierarchy_prepare.lua"),
                 vec![
                     Value::from(client_name), // client_name
                     Value::from(
@@ -3261,7 +4331,7 @@ where
         match conn
             .nvim
             .execute_lua(
-                include_str!("lua/lsp_type_hierarchy_supertypes.lua"),
+                lsp_script!("lua/lsp_type_hierarchy_supertypes.lua"),
                 vec![
                     Value::from(client_name), // client_name
                     Value::from(
@@ -3306,7 +4376,7 @@ where
         match conn
             .nvim
             .execute_lua(
-                include_str!("lua/lsp_type_hierarchy_subtypes.lua"),
+                lsp_script!("lua/lsp_type_hierarchy_subtypes.lua"),
                 vec![
                     Value::from(client_name), // client_name
                     Value::from(
@@ -3400,6 +4470,55 @@ mod tests {
     use super::*;
     use serde_json;
 
+    #[test]
+    fn test_workspace_edit_describe() {
+        let edit: WorkspaceEdit = serde_json::from_value(serde_json::json!({
+            "changes": {
+                "file:///src/main.rs": [
+                    {
+                        "range": {
+                            "start": {"line": 2, "character": 4},
+                            "end": {"line": 2, "character": 7}
+                        },
+                        "newText": "bar"
+                    },
+                    {
+                        "range": {
+                            "start": {"line": 9, "character": 0},
+                            "end": {"line": 9, "character": 0}
+                        },
+                        "newText": "use bar;\n"
+                    }
+                ]
+            },
+            "documentChanges": [
+                {"kind": "rename", "oldUri": "file:///src/foo.rs", "newUri": "file:///src/bar.rs"},
+                {
+                    "textDocument": {"uri": "file:///src/lib.rs", "version": 1},
+                    "edits": [
+                        {
+                            "range": {
+                                "start": {"line": 0, "character": 0},
+                                "end": {"line": 1, "character": 0}
+                            },
+                            "newText": ""
+                        }
+                    ]
+                }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(
+            edit.describe(),
+            "file:///src/main.rs:\n  \
+             3:5-3:8 replace with \"bar\"\n  \
+             10:1-10:1 insert \"use bar;\\n\"\n\
+             rename file:///src/foo.rs to file:///src/bar.rs\n\
+             file:///src/lib.rs:\n  \
+             1:1-2:1 delete"
+        );
+    }
+
     #[test]
     fn test_symbol_kind_serialization() {
         assert_eq!(serde_json::to_value(SymbolKind::Function).unwrap(), 12);
@@ -4057,6 +5176,85 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_published_notification_is_not_stored() {
+        let tracker = NotificationTracker::default();
+        let mut live = tracker.subscribe();
+
+        let waiter = tokio::spawn({
+            let tracker = tracker.clone();
+            async move {
+                tracker
+                    .wait_for_next_notification("changed", Duration::from_secs(5))
+                    .await
+            }
+        });
+        // Let the waiter register before publishing
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        tracker
+            .publish_notification("changed".to_string(), vec![])
+            .await;
+        assert_eq!(live.recv().await.unwrap().name, "changed");
+        assert_eq!(waiter.await.unwrap().unwrap().name, "changed");
+
+        let (count, _) = tracker.get_stats().await;
+        assert_eq!(count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_notification_custom_retention() {
+        let config = NeovimClientConfig::default().with_overrides(&NeovimClientConfigOverrides {
+            notification_max_stored: Some(8),
+            ..Default::default()
+        });
+        let tracker = NotificationTracker::with_retention(config.notification_retention());
+
+        for i in 0..20 {
+            tracker
+                .record_notification(format!("notification_{}", i), vec![Value::from(i as i64)])
+                .await;
+        }
+
+        let (count, _) = tracker.get_stats().await;
+        assert!(count <= 8);
+
+        // Older notifications were dropped, the most recent one is kept
+        let result = tracker
+            .wait_for_notification("notification_0", Duration::from_millis(10))
+            .await;
+        assert!(result.is_err());
+        let result = tracker
+            .wait_for_notification("notification_19", Duration::from_millis(10))
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_client_config_overrides() {
+        let base = NeovimClientConfig::default().with_overrides(&NeovimClientConfigOverrides {
+            lsp_timeout_ms: Some(10000),
+            read_window_lines: Some(500),
+            ..Default::default()
+        });
+        assert_eq!(base.lsp_timeout_ms, 10000);
+        assert_eq!(base.read_window_lines, Some(500));
+        assert_eq!(base.max_response_bytes, None);
+
+        // Overrides from a connect request fall back to the base configuration
+        let overrides: NeovimClientConfigOverrides =
+            serde_json::from_str(r#"{"max_response_bytes": 65536}"#).unwrap();
+        let config = base.with_overrides(&overrides);
+        assert_eq!(config.lsp_timeout_ms, 10000);
+        assert_eq!(config.read_window_lines, Some(500));
+        assert_eq!(config.max_response_bytes, Some(65536));
+        assert_eq!(config.notification_max_stored, MAX_STORED_NOTIFICATIONS);
+        assert_eq!(
+            config.notification_retention().expiry,
+            Duration::from_secs(NOTIFICATION_EXPIRY_SECONDS)
+        );
+    }
+
     #[tokio::test]
     async fn test_notification_expiry_in_wait() {
         let tracker = NotificationTracker::default();
@@ -4089,4 +5287,193 @@ mod tests {
                 .contains("Timeout waiting for notification")
         );
     }
+
+    #[test]
+    fn test_lsp_client_progress_deserialization() {
+        // vim.json.encode emits an empty Lua list as `{}`
+        let idle: LspClient = serde_json::from_str(
+            r#"{"id":1,"name":"gopls","pending_progress":{},"indexing":false}"#,
+        )
+        .unwrap();
+        assert!(idle.pending_progress.is_empty());
+        assert!(!idle.indexing);
+
+        let busy: LspClient = serde_json::from_str(
+            r#"{"id":2,"name":"rust-analyzer","pending_progress":[{"token":"rustAnalyzer/Indexing","title":"Indexing"}],"indexing":true}"#,
+        )
+        .unwrap();
+        assert_eq!(busy.pending_progress.len(), 1);
+        assert_eq!(busy.pending_progress[0].title.as_deref(), Some("Indexing"));
+        assert!(busy.indexing);
+
+        // Older payloads without progress fields still parse
+        let legacy: LspClient = serde_json::from_str(r#"{"id":3,"name":"luals"}"#).unwrap();
+        assert!(legacy.pending_progress.is_empty());
+        assert!(!legacy.indexing);
+    }
+
+    #[test]
+    fn test_symbol_locator_deserialization() {
+        #[derive(serde::Deserialize)]
+        struct Params {
+            #[serde(default, deserialize_with = "option_string_or_struct")]
+            symbol: Option<SymbolLocator>,
+        }
+
+        let params: Params = serde_json::from_str(r#"{"symbol": "Server::connect"}"#).unwrap();
+        assert_eq!(
+            params.symbol,
+            Some(SymbolLocator::Path("Server::connect".to_string()))
+        );
+
+        let text_match = Some(SymbolLocator::TextMatch {
+            line: 3,
+            text_match: "connect".to_string(),
+        });
+        let params: Params =
+            serde_json::from_str(r#"{"symbol": {"line": 3, "text_match": "connect"}}"#).unwrap();
+        assert_eq!(params.symbol, text_match);
+        let params: Params =
+            serde_json::from_str(r#"{"symbol": "{\"line\": 3, \"text_match\": \"connect\"}"}"#)
+                .unwrap();
+        assert_eq!(params.symbol, text_match);
+
+        let params: Params = serde_json::from_str("{}").unwrap();
+        assert_eq!(params.symbol, None);
+    }
+
+    #[test]
+    fn test_document_symbol_find_symbol() {
+        fn symbol(name: &str, line: u64, children: Vec<DocumentSymbol>) -> DocumentSymbol {
+            let range = Range {
+                start: Position { line, character: 4 },
+                end: Position {
+                    line,
+                    character: 10,
+                },
+            };
+            DocumentSymbol {
+                name: name.to_string(),
+                detail: None,
+                kind: SymbolKind::Function,
+                tags: None,
+                deprecated: None,
+                range: range.clone(),
+                selection_range: range,
+                children: Some(children),
+            }
+        }
+
+        let symbols = DocumentSymbolResult::Symbols(vec![
+            symbol("Server", 1, vec![]),
+            symbol("impl Server", 5, vec![symbol("connect", 6, vec![])]),
+            symbol(
+                "impl Drop for Client<T>",
+                10,
+                vec![symbol("connect", 11, vec![])],
+            ),
+            symbol("(*Handler).Serve", 20, vec![]),
+        ]);
+
+        let matches = symbols.find_symbol("Server::connect");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].0, "Server::connect");
+        assert_eq!(matches[0].1.line, 6);
+
+        assert_eq!(symbols.find_symbol("Client.connect")[0].1.line, 11);
+        assert_eq!(symbols.find_symbol("Handler.Serve")[0].1.line, 20);
+        assert_eq!(symbols.find_symbol("Serve")[0].1.line, 20);
+        // Bare names matching several symbols are ambiguous
+        assert_eq!(symbols.find_symbol("connect").len(), 2);
+        assert!(symbols.find_symbol("Server::missing").is_empty());
+        assert!(symbols.find_symbol("::").is_empty());
+    }
+
+    #[test]
+    fn test_lsp_client_names_deserialization() {
+        // vim.json.encode turns an empty list into an object
+        let selected: LspClientNames = serde_json::from_str(r#"{"names":{}}"#).unwrap();
+        assert!(selected.names.is_empty());
+
+        let selected: LspClientNames =
+            serde_json::from_str(r#"{"names":["gopls","golangci_lint_ls"]}"#).unwrap();
+        assert_eq!(selected.names, vec!["gopls", "golangci_lint_ls"]);
+    }
+
+    #[test]
+    fn test_lsp_client_details_deserialization() {
+        let client: LspClient = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "name": "gopls",
+            "root_dir": "/home/user/project",
+            "workspace_folders": [{"uri": "file:///home/user/project", "name": "/home/user/project"}],
+            "filetypes": ["go", "gomod"],
+            "attached_buffers": [1, 3],
+            "offset_encoding": "utf-16",
+            "initialized": true,
+            "capabilities": {"hover": true, "definition": true, "type_hierarchy": false},
+            "pending_progress": {},
+            "indexing": false,
+        }))
+        .unwrap();
+
+        assert_eq!(client.root_dir.as_deref(), Some("/home/user/project"));
+        assert_eq!(client.workspace_folders.len(), 1);
+        assert_eq!(client.filetypes, vec!["go", "gomod"]);
+        assert_eq!(client.attached_buffers, vec![1, 3]);
+        assert_eq!(client.offset_encoding.as_deref(), Some("utf-16"));
+        assert!(client.initialized);
+        assert!(client.capabilities.hover);
+        assert!(!client.capabilities.type_hierarchy);
+        // Capabilities missing from the summary default to unsupported
+        assert!(!client.capabilities.rename);
+    }
+
+    #[tokio::test]
+    async fn test_notification_tracker_wait_for_next_ignores_recorded() {
+        let tracker = NotificationTracker::default();
+
+        tracker
+            .record_notification("progress".to_string(), vec![Value::from("old")])
+            .await;
+
+        // Already-recorded notifications are not returned
+        let result = tracker
+            .wait_for_next_notification("progress", Duration::from_millis(50))
+            .await;
+        assert!(result.is_err());
+
+        let tracker_clone = tracker.clone();
+        let handle = tokio::spawn(async move {
+            tracker_clone
+                .wait_for_next_notification("progress", Duration::from_millis(500))
+                .await
+        });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        tracker
+            .record_notification("progress".to_string(), vec![Value::from("new")])
+            .await;
+
+        let notification = handle.await.unwrap().unwrap();
+        assert_eq!(notification.args[0].as_str().unwrap(), "new");
+    }
+
+    #[tokio::test]
+    async fn test_notification_tracker_subscribe() {
+        let tracker = NotificationTracker::default();
+        tracker
+            .record_notification("before".to_string(), vec![])
+            .await;
+
+        // Subscribers only see notifications recorded after subscribing
+        let mut receiver = tracker.subscribe();
+        tracker
+            .record_notification("after".to_string(), vec![])
+            .await;
+        assert_eq!(receiver.recv().await.unwrap().name, "after");
+
+        drop(tracker);
+        assert!(receiver.recv().await.is_err());
+    }
 }
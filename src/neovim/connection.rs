@@ -30,4 +30,9 @@ where
     pub fn target(&self) -> &str {
         &self.target
     }
+
+    /// Whether the RPC loop is still running, it stops once Neovim exits
+    pub fn is_alive(&self) -> bool {
+        !self.io_handler.is_finished()
+    }
 }
@@ -109,6 +109,42 @@ async fn test_lua_execution() {
     // Guard automatically cleans up when it goes out of scope
 }
 
+#[tokio::test]
+#[traced_test]
+#[cfg(any(unix, windows))]
+async fn test_teardown_autocmd() {
+    let ipc_path = generate_random_ipc_path();
+
+    let (client, _guard) = setup_auto_connected_client_ipc(&ipc_path).await;
+    let has_group =
+        "return pcall(vim.api.nvim_get_autocmds, { group = 'NVIM_MCP_DiagnosticsChanged' })";
+
+    let result = client.setup_autocmd().await;
+    assert!(result.is_ok(), "Failed to setup autocmd: {result:?}");
+    let result = client.execute_lua(has_group).await.unwrap();
+    assert_eq!(result.as_bool(), Some(true));
+
+    // A vim.notify installed after the setup is kept
+    client
+        .execute_lua("_G.test_notify = function() end; vim.notify = _G.test_notify")
+        .await
+        .unwrap();
+
+    let result = client.teardown_autocmd().await;
+    assert!(result.is_ok(), "Failed to remove autocmd: {result:?}");
+    let result = client.execute_lua(has_group).await.unwrap();
+    assert_eq!(result.as_bool(), Some(false));
+    let result = client
+        .execute_lua("return vim.notify == _G.test_notify")
+        .await
+        .unwrap();
+    assert_eq!(result.as_bool(), Some(true));
+
+    // Removing the group again is a no-op
+    let result = client.teardown_autocmd().await;
+    assert!(result.is_ok(), "Failed to remove autocmd twice: {result:?}");
+}
+
 #[tokio::test]
 #[traced_test]
 #[cfg(any(unix, windows))]
@@ -493,6 +529,24 @@ func main() {
     info!("LSP clients: {:?}", lsp_clients);
     assert!(!lsp_clients.is_empty(), "No LSP clients found");
 
+    // Client details should describe the attached gopls server
+    let gopls = lsp_clients
+        .iter()
+        .find(|c| c.name == "gopls")
+        .expect("gopls client should be listed");
+    assert!(gopls.initialized, "gopls should be initialized");
+    assert!(
+        gopls.capabilities.definition,
+        "gopls should support definition"
+    );
+    assert!(
+        gopls.attached_buffers.contains(&1),
+        "gopls should be attached to the opened buffer: {:?}",
+        gopls.attached_buffers
+    );
+    assert!(gopls.offset_encoding.is_some());
+    assert!(gopls.filetypes.contains(&"go".to_string()));
+
     // Test definition lookup for sayHello function call on line 9 (0-indexed)
     // Position cursor on "sayHello" in the function call
     let result = client
@@ -1358,3 +1412,67 @@ async fn test_lsp_range_formatting_and_apply_edits() {
         "Buffer content should have changed after applying text edits"
     );
 }
+
+#[tokio::test]
+#[traced_test]
+async fn test_lsp_position_conversion() {
+    let ipc_path = generate_random_ipc_path();
+    let (client, _guard) = setup_auto_connected_client_ipc(&ipc_path).await;
+
+    let temp_dir = TempDir::new().unwrap();
+    let main_path = temp_dir.path().join("main.rs");
+    let other_path = temp_dir.path().join("other.rs");
+    fs::write(&main_path, "😀😀\n中文ab\n").unwrap();
+    fs::write(&other_path, "ab\n😀x\n").unwrap();
+    let main_uri = format!("file://{}", main_path.display());
+    let other_uri = format!("file://{}", other_path.display());
+
+    let value = serde_json::json!({
+        "range": {"start": {"line": 0, "character": 2}, "end": {"line": 1, "character": 3}},
+        "changes": {
+            &other_uri: [
+                {"range": {"start": {"line": 0, "character": 2}, "end": {"line": 0, "character": 2}}}
+            ]
+        },
+        "from": {"uri": &other_uri},
+        "fromRanges": [{"start": {"line": 1, "character": 1}, "end": {"line": 1, "character": 1}}]
+    });
+    let code = format!(
+        r#"{prelude}
+local value = vim.json.decode([[{value}]])
+lsp_convert_positions(value, "{main_uri}", TOOL_ENCODING, "utf-16")
+-- "é" is 2 UTF-8 bytes, "中" is 3, "😀" is 4 bytes and 2 UTF-16 units
+local line = "aé中😀b"
+return vim.json.encode({{
+    value = value,
+    characters = {{
+        lsp_convert_character(line, 4, "utf-32", "utf-16"),
+        lsp_convert_character(line, 4, "utf-32", "utf-8"),
+        lsp_convert_character(line, 5, "utf-16", "utf-32"),
+        lsp_convert_character(line, 10, "utf-8", "utf-32"),
+        lsp_convert_character(line, 4, "utf-16", "utf-32"),
+        lsp_convert_character(line, 7, "utf-32", "utf-16"),
+        lsp_convert_character("", 3, "utf-32", "utf-8"),
+    }},
+}})
+"#,
+        prelude = include_str!("lua/lsp_client.lua"),
+    );
+    let result = client.execute_lua(&code).await.unwrap();
+    let result: serde_json::Value = serde_json::from_str(result.as_str().unwrap()).unwrap();
+
+    let value = &result["value"];
+    assert_eq!(value["range"]["start"]["character"], 4);
+    assert_eq!(value["range"]["end"]["character"], 3);
+    assert_eq!(
+        value["changes"][&other_uri][0]["range"]["start"]["character"],
+        2
+    );
+    assert_eq!(value["fromRanges"][0]["start"]["character"], 2);
+    // Offsets inside a character snap to its start, offsets past the end of
+    // the line keep their overflow
+    assert_eq!(
+        result["characters"],
+        serde_json::json!([5, 10, 4, 4, 3, 8, 3])
+    );
+}
@@ -0,0 +1,23 @@
+local id, message = unpack({ ... })
+
+-- Prompts still waiting for their answer, removed by the server when it stops
+-- waiting so that a late answer is not taken for approval
+_G.NVIM_MCP_CONFIRMATIONS = _G.NVIM_MCP_CONFIRMATIONS or {}
+_G.NVIM_MCP_CONFIRMATIONS[id] = true
+
+-- The default vim.ui.select blocks until answered, so ask once this request returned
+vim.schedule(function()
+    vim.ui.select({ "Approve", "Reject" }, {
+        prompt = message,
+    }, function(choice)
+        if not _G.NVIM_MCP_CONFIRMATIONS[id] then
+            vim.notify("nvim-mcp: the confirmation request expired, nothing was changed", vim.log.levels.WARN)
+            return
+        end
+        _G.NVIM_MCP_CONFIRMATIONS[id] = nil
+        vim.rpcnotify(0, "NVIM_MCP_Confirmed", {
+            id = id,
+            approved = choice == "Approve",
+        })
+    end)
+end)
@@ -0,0 +1,33 @@
+local prefix = ...
+local dir, partial = prefix:match("^(.*/)([^/]*)$")
+if not dir then
+    dir, partial = "", prefix
+end
+
+local ans = {}
+-- Only complete paths inside the project root
+local inside_project = dir:sub(1, 1) ~= "/"
+for segment in dir:gmatch("[^/]+") do
+    if segment == ".." then
+        inside_project = false
+    end
+end
+
+local handle = inside_project and vim.uv.fs_scandir(vim.fs.joinpath(vim.fn.getcwd(), dir))
+while handle do
+    local name, type = vim.uv.fs_scandir_next(handle)
+    if not name then
+        break
+    end
+    -- Hidden entries only when asked for
+    local hidden = name:sub(1, 1) == "." and partial:sub(1, 1) ~= "."
+    if not hidden and name:sub(1, #partial) == partial then
+        table.insert(ans, dir .. name .. (type == "directory" and "/" or ""))
+    end
+end
+table.sort(ans)
+
+if #ans == 0 then
+    return "[]"
+end
+return vim.json.encode(ans)
@@ -0,0 +1,26 @@
+local diagnostics = vim.diagnostic.get(...)
+
+-- Diagnostic columns are byte offsets, tools count Unicode code points
+local function to_character(bufnr, lnum, col)
+    local line = vim.api.nvim_buf_get_lines(bufnr, lnum, lnum + 1, false)[1]
+    if line == nil then
+        return col
+    end
+    local clamped = math.min(col, #line)
+    return vim.str_utfindex(line, "utf-32", clamped, false) + (col - clamped)
+end
+
+for _, diagnostic in ipairs(diagnostics) do
+    diagnostic.col = to_character(diagnostic.bufnr, diagnostic.lnum, diagnostic.col)
+    diagnostic.end_col = to_character(diagnostic.bufnr, diagnostic.end_lnum, diagnostic.end_col)
+    -- Keep the original LSP range consistent with the converted columns
+    local lsp = vim.tbl_get(diagnostic, "user_data", "lsp")
+    if lsp ~= nil then
+        lsp.range = {
+            start = { line = diagnostic.lnum, character = diagnostic.col },
+            ["end"] = { line = diagnostic.end_lnum, character = diagnostic.end_col },
+        }
+    end
+end
+
+return vim.json.encode(diagnostics)
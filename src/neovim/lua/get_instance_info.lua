@@ -0,0 +1,7 @@
+return vim.json.encode({
+    pid = vim.fn.getpid(),
+    nvim_version = tostring(vim.version()),
+    cwd = vim.fn.getcwd(),
+    current_buffer = vim.api.nvim_buf_get_name(0),
+    plugin_loaded = package.loaded["nvim-mcp"] ~= nil,
+})
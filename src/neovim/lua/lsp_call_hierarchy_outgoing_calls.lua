@@ -1,18 +1,10 @@
-local clients = vim.lsp.get_clients()
 local client_name, params_raw, timeout_ms = unpack({ ... })
-local client
-for _, v in ipairs(clients) do
-    if v.name == client_name then
-        client = v
-    end
-end
+local client, err_msg = lsp_get_client(client_name, "callHierarchy/outgoingCalls", lsp_params_bufnr(params_raw))
 if client == nil then
-    return vim.json.encode({
-        err_msg = string.format("LSP client %s not found", vim.json.encode(client_name)),
-    })
+    return vim.json.encode({ err_msg = err_msg })
 end
 
-local params = vim.json.decode(params_raw)
+local params, uri = lsp_client_params(client, params_raw)
 local result, err = client:request_sync("callHierarchy/outgoingCalls", params, timeout_ms)
 if err then
     return vim.json.encode({
@@ -24,4 +16,4 @@ if err then
     })
 end
 
-return vim.json.encode(result)
+return vim.json.encode(lsp_tool_result(client, result, uri))
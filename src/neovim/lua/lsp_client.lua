@@ -0,0 +1,213 @@
+-- Prepended to the LSP scripts, which look their client up through `lsp_get_client`
+
+-- Find the client named `client_name` that supports `method` when given,
+-- preferring the clients attached to `bufnr` when several share the name
+local function lsp_get_client(client_name, method, bufnr)
+    local clients = bufnr ~= nil and vim.lsp.get_clients({ name = client_name, bufnr = bufnr }) or {}
+    if #clients == 0 then
+        clients = vim.lsp.get_clients({ name = client_name })
+    end
+    if #clients == 0 then
+        return nil, string.format("LSP client %s not found", vim.json.encode(client_name))
+    end
+    for _, client in ipairs(clients) do
+        if method == nil or client:supports_method(method, bufnr) then
+            return client
+        end
+    end
+    return nil, string.format("LSP client %s does not support %s", vim.json.encode(client_name), method)
+end
+
+-- Tool inputs and outputs count `character` offsets in Unicode code points
+local TOOL_ENCODING = "utf-32"
+
+local function lsp_encoding(client)
+    return client.offset_encoding or "utf-16"
+end
+
+-- Width in `encoding` units of a UTF-8 sequence of `len` bytes
+local function encoding_units(len, encoding)
+    if encoding == "utf-8" then
+        return len
+    elseif encoding == "utf-16" then
+        return len == 4 and 2 or 1
+    end
+    return 1
+end
+
+-- Convert a character offset on `line` between encodings. Offsets inside a
+-- multi-unit character snap to its start, offsets past the end of the line
+-- keep their overflow unchanged.
+local function lsp_convert_character(line, character, from, to)
+    if from == to then
+        return character
+    end
+    local from_units, to_units, i = 0, 0, 1
+    while i <= #line do
+        local byte = line:byte(i)
+        local len = byte < 0xC0 and 1 or byte < 0xE0 and 2 or byte < 0xF0 and 3 or 4
+        local width = encoding_units(len, from)
+        if from_units + width > character then
+            return to_units
+        end
+        from_units = from_units + width
+        to_units = to_units + encoding_units(len, to)
+        i = i + len
+    end
+    return to_units + (character - from_units)
+end
+
+local function uri_of(value)
+    return type(value) == "table" and type(value.uri) == "string" and value.uri or nil
+end
+
+-- Loaded buffer of the document a JSON tool request is about, if any
+local function lsp_params_bufnr(params_raw)
+    local params = vim.json.decode(params_raw)
+    local uri = uri_of(params.textDocument) or uri_of(params.item)
+    if uri == nil then
+        return nil
+    end
+    local ok, fname = pcall(vim.uri_to_fname, uri)
+    local bufnr = ok and vim.fn.bufnr(fname) or -1
+    return bufnr > 0 and bufnr or nil
+end
+
+-- Visit every LSP position (`{line, character}` table) in `value` together with
+-- the URI of the document it refers to, `uri` for positions without their own
+local function lsp_visit_positions(value, uri, f)
+    if type(value) ~= "table" then
+        return
+    end
+    if type(value.line) == "number" and type(value.character) == "number" then
+        f(uri, value)
+        return
+    end
+    local own_uri = uri_of(value)
+        or (type(value.targetUri) == "string" and value.targetUri)
+        or uri_of(value.textDocument)
+        or uri
+    -- Incoming calls report `fromRanges` inside the calling item
+    local from_uri = uri_of(value.from) or uri
+    for key, child in pairs(value) do
+        if key == "changes" and type(child) == "table" then
+            for change_uri, edits in pairs(child) do
+                lsp_visit_positions(edits, change_uri, f)
+            end
+        elseif key == "originSelectionRange" then
+            lsp_visit_positions(child, uri, f)
+        elseif key == "fromRanges" then
+            lsp_visit_positions(child, from_uri, f)
+        else
+            lsp_visit_positions(child, own_uri, f)
+        end
+    end
+end
+
+-- Read the zero-based `line_numbers` of a document, from its loaded buffer or
+-- from disk up to the last requested line, without creating a buffer
+local function read_lines(uri, line_numbers)
+    local lines = {}
+    local ok, fname = pcall(vim.uri_to_fname, uri)
+    if not ok then
+        return lines
+    end
+    local bufnr = vim.fn.bufnr(fname)
+    if bufnr > 0 and vim.api.nvim_buf_is_loaded(bufnr) then
+        for lnum in pairs(line_numbers) do
+            lines[lnum] = vim.api.nvim_buf_get_lines(bufnr, lnum, lnum + 1, false)[1]
+        end
+        return lines
+    end
+    local file = io.open(fname, "r")
+    if file == nil then
+        return lines
+    end
+    local last = 0
+    for lnum in pairs(line_numbers) do
+        last = math.max(last, lnum)
+    end
+    local lnum = 0
+    for line in file:lines() do
+        if line_numbers[lnum] then
+            lines[lnum] = line
+        end
+        if lnum >= last then
+            break
+        end
+        lnum = lnum + 1
+    end
+    file:close()
+    return lines
+end
+
+-- Convert every position in `value` in place between encodings
+local function lsp_convert_positions(value, uri, from, to)
+    if from == to then
+        return
+    end
+    local requested = {}
+    lsp_visit_positions(value, uri, function(position_uri, position)
+        requested[position_uri] = requested[position_uri] or {}
+        requested[position_uri][position.line] = true
+    end)
+    local lines = {}
+    for position_uri, line_numbers in pairs(requested) do
+        lines[position_uri] = read_lines(position_uri, line_numbers)
+    end
+    lsp_visit_positions(value, uri, function(position_uri, position)
+        local line = lines[position_uri][position.line] or ""
+        position.character = lsp_convert_character(line, position.character, from, to)
+    end)
+end
+
+-- Decode the JSON request of a tool, converting its positions to the client's encoding.
+-- Returns the request and the URI of the document it is about.
+local function lsp_client_params(client, params_raw)
+    local params = vim.json.decode(params_raw)
+    local document = params.textDocument or params.item
+    local uri = uri_of(document) or uri_of(params) or ""
+    lsp_convert_positions(params, uri, TOOL_ENCODING, lsp_encoding(client))
+    return params, uri
+end
+
+-- Convert the positions of a response from the client's encoding back to the tool encoding
+local function lsp_tool_result(client, result, uri)
+    lsp_convert_positions(result, uri, lsp_encoding(client), TOOL_ENCODING)
+    return result
+end
+
+-- Send `method` with the tool request `params_raw` and wait for the response,
+-- registering the request under `request_key` so the server can cancel it while
+-- it is waited on. Returns the JSON encoded response in the tool encoding.
+local function lsp_cancellable_request(client, method, params_raw, timeout_ms, request_key, bufnr)
+    local params, uri = lsp_client_params(client, params_raw)
+    local response
+    local ok, request_id = client:request(method, params, function(err, result)
+        response = { err = err, result = result }
+    end, bufnr)
+    if not ok then
+        return vim.json.encode({
+            err_msg = string.format("LSP client %s request error: client has stopped", vim.json.encode(client.name)),
+        })
+    end
+
+    _G.NVIM_MCP_LSP_REQUESTS = _G.NVIM_MCP_LSP_REQUESTS or {}
+    _G.NVIM_MCP_LSP_REQUESTS[request_key] = function()
+        client:cancel_request(request_id)
+        response = response or { err = { code = -32800, message = "Request cancelled" } }
+    end
+    local done = vim.wait(timeout_ms, function()
+        return response ~= nil
+    end, 10)
+    _G.NVIM_MCP_LSP_REQUESTS[request_key] = nil
+    if not done then
+        client:cancel_request(request_id)
+        return vim.json.encode({
+            err_msg = string.format("LSP client %s request error: timeout", vim.json.encode(client.name)),
+        })
+    end
+
+    return vim.json.encode(lsp_tool_result(client, response, uri))
+end
+
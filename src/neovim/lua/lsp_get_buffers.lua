@@ -4,10 +4,16 @@ local ans = {}
 for _, id in ipairs(all_bufs) do
     local name = vim.api.nvim_buf_get_name(id)
     local line_count = vim.api.nvim_buf_line_count(id)
+    local loaded = vim.api.nvim_buf_is_loaded(id)
     table.insert(ans, {
         id = id,
         name = name,
         line_count = line_count,
+        filetype = vim.bo[id].filetype,
+        listed = vim.bo[id].buflisted,
+        loaded = loaded,
+        -- Size in bytes of the buffer contents, unknown until loaded
+        size = loaded and vim.api.nvim_buf_get_offset(id, line_count) or 0,
     })
 end
 
@@ -1,5 +1,70 @@
 local clients = vim.lsp.get_clients()
+
+-- LSP methods summarized in the `capabilities` field, keyed by a normalized name
+local capability_methods = {
+    hover = "textDocument/hover",
+    definition = "textDocument/definition",
+    declaration = "textDocument/declaration",
+    type_definition = "textDocument/typeDefinition",
+    implementation = "textDocument/implementation",
+    references = "textDocument/references",
+    document_symbol = "textDocument/documentSymbol",
+    workspace_symbol = "workspace/symbol",
+    code_action = "textDocument/codeAction",
+    code_action_resolve = "codeAction/resolve",
+    rename = "textDocument/rename",
+    prepare_rename = "textDocument/prepareRename",
+    formatting = "textDocument/formatting",
+    range_formatting = "textDocument/rangeFormatting",
+    call_hierarchy = "textDocument/prepareCallHierarchy",
+    type_hierarchy = "textDocument/prepareTypeHierarchy",
+}
+
+local function capabilities(client)
+    local summary = {}
+    for key, method in pairs(capability_methods) do
+        summary[key] = client:supports_method(method)
+    end
+    return summary
+end
+
+-- Work-done progress tokens that have begun but not yet ended.
+-- Neovim tracks these in `client.progress.pending` (token -> title).
+local function pending_progress(client)
+    local pending = {}
+    if client.progress and type(client.progress.pending) == "table" then
+        for token, title in pairs(client.progress.pending) do
+            table.insert(pending, { token = tostring(token), title = title })
+        end
+    end
+    return pending
+end
+
+local function attached_buffers(client)
+    local buffers = {}
+    for bufnr, attached in pairs(client.attached_buffers or {}) do
+        if attached then
+            table.insert(buffers, bufnr)
+        end
+    end
+    table.sort(buffers)
+    return buffers
+end
+
 local result = vim.tbl_map(function(client)
-    return { name = client.name, id = client.id }
+    local pending = pending_progress(client)
+    return {
+        name = client.name,
+        id = client.id,
+        root_dir = client.root_dir,
+        workspace_folders = client.workspace_folders,
+        filetypes = client.config and client.config.filetypes,
+        attached_buffers = attached_buffers(client),
+        offset_encoding = client.offset_encoding,
+        initialized = client.initialized == true,
+        capabilities = capabilities(client),
+        pending_progress = pending,
+        indexing = #pending > 0,
+    }
 end, clients)
 return vim.json.encode(result)
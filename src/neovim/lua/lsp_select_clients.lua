@@ -0,0 +1,50 @@
+local method, bufnr, path = unpack({ ... })
+if bufnr == vim.NIL then
+    bufnr = nil
+end
+if path == vim.NIL then
+    path = nil
+end
+
+-- Documents referenced by path may already be loaded in a buffer
+if bufnr == nil and path ~= nil then
+    local loaded = vim.fn.bufnr(path)
+    if loaded > 0 then
+        bufnr = loaded
+    end
+end
+if bufnr == 0 then
+    bufnr = vim.api.nvim_get_current_buf()
+end
+
+local names = {}
+local function add(client)
+    if client:supports_method(method, bufnr) and not vim.tbl_contains(names, client.name) then
+        table.insert(names, client.name)
+    end
+end
+
+-- Prefer clients attached to the target buffer
+if bufnr ~= nil then
+    for _, client in ipairs(vim.lsp.get_clients({ bufnr = bufnr })) do
+        add(client)
+    end
+end
+
+-- Otherwise fall back to clients configured for the document's filetype
+if #names == 0 then
+    local filetype
+    if path ~= nil then
+        filetype = vim.filetype.match({ filename = path })
+    elseif bufnr ~= nil then
+        filetype = vim.bo[bufnr].filetype
+    end
+    for _, client in ipairs(vim.lsp.get_clients()) do
+        local filetypes = client.config and client.config.filetypes
+        if filetype == nil or filetypes == nil or vim.tbl_contains(filetypes, filetype) then
+            add(client)
+        end
+    end
+end
+
+return vim.json.encode({ names = names })
@@ -1,7 +1,7 @@
 local params_raw = ...
 local params = vim.json.decode(params_raw)
 
--- Convert to 1-based row for Vim (col is already 0-based)
+-- Convert to 1-based row for Vim (col stays 0-based)
 params.position.line = params.position.line + 1
 
 -- Function to navigate to file by URI
@@ -41,8 +41,16 @@ end
 -- Set the cursor position
 local current_buf = vim.api.nvim_get_current_buf()
 
--- Set cursor position
-vim.api.nvim_win_set_cursor(0, { params.position.line, params.position.character })
+-- Set cursor position, converting the code point offset to the byte column Neovim expects
+local target_line = vim.api.nvim_buf_get_lines(
+    current_buf,
+    params.position.line - 1,
+    params.position.line,
+    false
+)[1] or ""
+local character = math.min(params.position.character, vim.str_utfindex(target_line, "utf-32"))
+local col = vim.str_byteindex(target_line, "utf-32", character, false)
+vim.api.nvim_win_set_cursor(0, { params.position.line, col })
 
 -- Return success information
 local current_bufname = vim.api.nvim_buf_get_name(current_buf)
@@ -10,6 +10,16 @@ vim.api.nvim_create_autocmd("DiagnosticChanged", {
     end,
 })
 
+vim.api.nvim_create_autocmd({ "TextChanged", "TextChangedI", "BufReadPost" }, {
+    group = group,
+    callback = function(args)
+        vim.rpcnotify(0, "NVIM_MCP_BufferChanged", {
+            buf = args.buf,
+            changedtick = vim.api.nvim_buf_get_changedtick(args.buf),
+        })
+    end,
+})
+
 vim.api.nvim_create_autocmd("LspAttach", {
     group = group,
     callback = function(args)
@@ -46,4 +56,92 @@ vim.api.nvim_create_autocmd("LspDetach", {
     end,
 })
 
+vim.api.nvim_create_autocmd("LspProgress", {
+    group = group,
+    callback = function(args)
+        local client_id = args.data.client_id
+        local client = vim.lsp.get_client_by_id(client_id)
+        local params = args.data.params or {}
+        local value = type(params.value) == "table" and params.value or {}
+
+        if client then
+            vim.rpcnotify(0, "NVIM_MCP_LspProgress", {
+                client_name = client.name,
+                client_id = client_id,
+                token = params.token,
+                kind = value.kind,
+                title = value.title,
+                message = value.message,
+                percentage = value.percentage,
+            })
+        end
+    end,
+})
+
+-- Every connected server shares the autocmds and wrappers, teardown keeps them
+-- until the last of their channels is gone
+_G.NVIM_MCP_CHANNELS = _G.NVIM_MCP_CHANNELS or {}
+_G.NVIM_MCP_CHANNELS[vim.api.nvim_get_api_info()[1]] = true
+
+-- Forward vim.notify and LSP window messages, wrapping them only once so that
+-- setting up again neither wraps them twice nor replaces later handlers
+if _G.NVIM_MCP_ORIGINALS == nil then
+    local originals = {
+        notify = vim.notify,
+        log_message = vim.lsp.handlers["window/logMessage"],
+        show_message = vim.lsp.handlers["window/showMessage"],
+    }
+
+    -- Set while an LSP message is shown, which may go through vim.notify
+    local forwarding_lsp_message = false
+
+    local function notify(msg, level, opts)
+        if not forwarding_lsp_message then
+            vim.rpcnotify(0, "NVIM_MCP_Message", {
+                source = "notify",
+                level = level or vim.log.levels.INFO,
+                message = tostring(msg),
+            })
+        end
+        return originals.notify(msg, level, opts)
+    end
+
+    -- LSP MessageType: Error, Warning, Info, Log
+    local lsp_message_levels = {
+        vim.log.levels.ERROR,
+        vim.log.levels.WARN,
+        vim.log.levels.INFO,
+        vim.log.levels.DEBUG,
+    }
+
+    local function forward_lsp_message(original)
+        return function(err, result, ctx, config)
+            local client = vim.lsp.get_client_by_id(ctx.client_id)
+            vim.rpcnotify(0, "NVIM_MCP_Message", {
+                source = "lsp",
+                client_name = client and client.name,
+                level = lsp_message_levels[result.type] or vim.log.levels.INFO,
+                message = result.message,
+            })
+            forwarding_lsp_message = true
+            local ok, rv = pcall(original, err, result, ctx, config)
+            forwarding_lsp_message = false
+            if not ok then
+                error(rv)
+            end
+            return rv
+        end
+    end
+
+    _G.NVIM_MCP_ORIGINALS = originals
+    _G.NVIM_MCP_WRAPPERS = {
+        notify = notify,
+        log_message = forward_lsp_message(originals.log_message),
+        show_message = forward_lsp_message(originals.show_message),
+    }
+    vim.notify = _G.NVIM_MCP_WRAPPERS.notify
+    vim.lsp.handlers["window/logMessage"] = _G.NVIM_MCP_WRAPPERS.log_message
+    vim.lsp.handlers["window/showMessage"] = _G.NVIM_MCP_WRAPPERS.show_message
+end
+
 vim.rpcnotify(0, "NVIM_MCP", "setup diagnostics changed autocmd")
@@ -0,0 +1,31 @@
+-- Release the setup of this channel, forgetting channels that closed without one
+local channels = _G.NVIM_MCP_CHANNELS or {}
+channels[vim.api.nvim_get_api_info()[1]] = nil
+for chan in pairs(channels) do
+    if vim.api.nvim_get_chan_info(chan).id == nil then
+        channels[chan] = nil
+    end
+end
+if next(channels) ~= nil then
+    return
+end
+_G.NVIM_MCP_CHANNELS = nil
+
+pcall(vim.api.nvim_del_augroup_by_name, "NVIM_MCP_DiagnosticsChanged")
+
+-- Restore the functions wrapped to forward messages, unless they were
+-- replaced since
+local originals, wrappers = _G.NVIM_MCP_ORIGINALS, _G.NVIM_MCP_WRAPPERS
+if originals and wrappers then
+    if vim.notify == wrappers.notify then
+        vim.notify = originals.notify
+    end
+    if vim.lsp.handlers["window/logMessage"] == wrappers.log_message then
+        vim.lsp.handlers["window/logMessage"] = originals.log_message
+    end
+    if vim.lsp.handlers["window/showMessage"] == wrappers.show_message then
+        vim.lsp.handlers["window/showMessage"] = originals.show_message
+    end
+end
+_G.NVIM_MCP_ORIGINALS = nil
+_G.NVIM_MCP_WRAPPERS = nil
@@ -6,8 +6,10 @@ mod error;
 pub mod integration_tests;
 
 pub use client::{
-    CallHierarchyItem, CodeAction, DocumentIdentifier, FormattingOptions, NeovimClient,
-    NeovimClientTrait, Position, PrepareRenameResult, Range, WorkspaceEdit, string_or_struct,
+    CallHierarchyItem, CodeAction, DocumentIdentifier, EMBED_TARGET_PREFIX, FormattingOptions,
+    InstanceInfo, NeovimClient, NeovimClientConfig, NeovimClientConfigOverrides, NeovimClientTrait,
+    Position, PrepareRenameResult, Range, SymbolLocator, WorkspaceEdit, option_string_or_struct,
+    string_or_struct,
 };
 
 pub use error::NeovimError;
@@ -0,0 +1,175 @@
+use rmcp::{
+    ErrorData as McpError,
+    model::{CompleteRequestParams, CompletionInfo, Reference},
+};
+use tracing::debug;
+
+use super::core::NeovimMcpServer;
+use super::resources::resource_templates;
+
+/// Upper bound for querying Neovim while completing, so that slow instances
+/// do not hold up interactive clients
+const COMPLETION_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Keep the candidates starting with `value`, up to the number a response may hold
+fn completion_info(candidates: Vec<String>, value: &str) -> CompletionInfo {
+    let mut values: Vec<String> = candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(value))
+        .collect();
+    values.sort();
+    values.dedup();
+
+    let total = values.len();
+    values.truncate(CompletionInfo::MAX_VALUES);
+    CompletionInfo {
+        has_more: Some(total > values.len()),
+        total: u32::try_from(total).ok(),
+        values,
+    }
+}
+
+/// Names of the `{variables}` of a URI template
+fn template_variables(uri_template: &str) -> Vec<String> {
+    uri_template
+        .split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}'))
+        .map(|(variable, _)| variable.to_string())
+        .collect()
+}
+
+impl NeovimMcpServer {
+    /// Arguments of the prompt or variables of the resource template a
+    /// completion request refers to
+    fn reference_arguments(&self, reference: &Reference) -> Vec<String> {
+        match reference {
+            Reference::Prompt(prompt) => self
+                .prompt_router
+                .list_all()
+                .into_iter()
+                .chain(self.list_lua_prompts())
+                .find(|candidate| candidate.name == prompt.name)
+                .and_then(|prompt| prompt.arguments)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|argument| argument.name)
+                .collect(),
+            Reference::Resource(resource) => resource_templates()
+                .into_iter()
+                .find(|template| template.raw.uri_template == resource.uri)
+                .map(|template| template_variables(&template.raw.uri_template))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Complete an argument of the prompt or resource template the request
+    /// refers to. The same names are used everywhere, e.g. `connection_id`
+    /// and `buffer_id`, so prompts and resources share their completions
+    pub async fn complete_argument(
+        &self,
+        request: &CompleteRequestParams,
+    ) -> Result<CompletionInfo, McpError> {
+        let name = request.argument.name.as_str();
+        let value = request.argument.value.as_str();
+        debug!("Completing argument '{name}' from '{value}'");
+
+        if !self
+            .reference_arguments(&request.r#ref)
+            .iter()
+            .any(|argument| argument == name)
+        {
+            return Ok(CompletionInfo::default());
+        }
+
+        let candidates = match name {
+            "connection_id" => self
+                .nvim_clients
+                .iter()
+                .map(|entry| entry.key().clone())
+                .collect(),
+            "buffer_id" | "lsp_client_name" | "path" => {
+                // Completed from the connection chosen in an earlier argument
+                let connection_id = request
+                    .context
+                    .as_ref()
+                    .and_then(|context| context.get_argument("connection_id"))
+                    .map(String::as_str);
+                let Ok(connection_id) = self.resolve_connection_id(connection_id) else {
+                    return Ok(CompletionInfo::default());
+                };
+                let Ok(client) = self.get_connection(Some(&connection_id)) else {
+                    return Ok(CompletionInfo::default());
+                };
+                if client.is_stale() {
+                    return Ok(CompletionInfo::default());
+                }
+
+                let candidates = async {
+                    match name {
+                        "buffer_id" => client.get_buffers().await.map(|buffers| {
+                            buffers
+                                .into_iter()
+                                .filter(|buffer| buffer.listed)
+                                .map(|buffer| buffer.id.to_string())
+                                .collect()
+                        }),
+                        "lsp_client_name" => client
+                            .lsp_get_clients()
+                            .await
+                            .map(|clients| clients.into_iter().map(|client| client.name).collect()),
+                        _ => client.complete_project_path(value).await,
+                    }
+                };
+                match tokio::time::timeout(COMPLETION_TIMEOUT, candidates).await {
+                    Ok(Ok(candidates)) => candidates,
+                    Ok(Err(e)) => {
+                        debug!("Failed to complete '{name}' for connection '{connection_id}': {e}");
+                        vec![]
+                    }
+                    Err(_) => {
+                        debug!("Timed out completing '{name}' for connection '{connection_id}'");
+                        vec![]
+                    }
+                }
+            }
+            _ => vec![],
+        };
+        Ok(completion_info(candidates, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completion_info() {
+        let info = completion_info(
+            vec![
+                "b".to_string(),
+                "ab".to_string(),
+                "a".to_string(),
+                "a".to_string(),
+            ],
+            "a",
+        );
+        assert_eq!(info.values, vec!["a", "ab"]);
+        assert_eq!(info.total, Some(2));
+        assert_eq!(info.has_more, Some(false));
+
+        let info = completion_info((0..150).map(|i| i.to_string()).collect(), "");
+        assert_eq!(info.values.len(), CompletionInfo::MAX_VALUES);
+        assert_eq!(info.total, Some(150));
+        assert_eq!(info.has_more, Some(true));
+    }
+
+    #[test]
+    fn test_template_variables() {
+        assert_eq!(
+            template_variables("nvim-diagnostics://{connection_id}/buffer/{buffer_id}"),
+            vec!["connection_id", "buffer_id"]
+        );
+        assert!(template_variables("nvim-connections://").is_empty());
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+use rmcp::{
+    ErrorData as McpError, RoleServer,
+    model::{
+        BooleanSchema, CreateElicitationRequestParams, ElicitationAction, ElicitationSchema,
+        PrimitiveSchema,
+    },
+    service::{ElicitationMode, Peer},
+};
+use tracing::debug;
+
+use crate::neovim::NeovimClientTrait;
+
+use super::core::NeovimMcpServer;
+
+/// How long a confirmation waits for an answer before the tool call is rejected
+const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Tools that change the connected editor, and so can require a confirmation
+pub const CONFIRMABLE_TOOLS: &[&str] = &[
+    "exec_lua",
+    "lsp_apply_edit",
+    "lsp_rename",
+    "lsp_formatting",
+    "lsp_range_formatting",
+    "lsp_organize_imports",
+];
+
+/// Where the user is asked to confirm a tool call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmMethod {
+    /// MCP elicitation, falling back to Neovim when the client does not support it
+    Elicit,
+    /// A `vim.ui.select` prompt in the connected Neovim instance
+    Neovim,
+}
+
+impl FromStr for ConfirmMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "elicit" => Ok(ConfirmMethod::Elicit),
+            "nvim" => Ok(ConfirmMethod::Neovim),
+            method => Err(format!(
+                "Invalid confirmation method: '{method}'. Must be 'elicit' or 'nvim'"
+            )),
+        }
+    }
+}
+
+/// The tools that only run once the user approved them
+#[derive(Debug, Clone, Default)]
+pub struct ConfirmationPolicy {
+    tools: HashMap<String, ConfirmMethod>,
+}
+
+impl ConfirmationPolicy {
+    /// Parse `TOOL[=METHOD]` entries, where `all` stands for every confirmable
+    /// tool and the method defaults to `elicit`. Later entries take precedence.
+    pub fn parse<S: AsRef<str>>(entries: &[S]) -> Result<Self, String> {
+        let mut tools = HashMap::new();
+        for entry in entries {
+            let (tool, method) = match entry.as_ref().split_once('=') {
+                Some((tool, method)) => (tool, method.parse()?),
+                None => (entry.as_ref(), ConfirmMethod::Elicit),
+            };
+            if tool == "all" {
+                for tool in CONFIRMABLE_TOOLS {
+                    tools.insert(tool.to_string(), method);
+                }
+            } else if CONFIRMABLE_TOOLS.contains(&tool) {
+                tools.insert(tool.to_string(), method);
+            } else {
+                return Err(format!(
+                    "Tool '{tool}' cannot require confirmation. Must be 'all' or one of: {}",
+                    CONFIRMABLE_TOOLS.join(", ")
+                ));
+            }
+        }
+        Ok(Self { tools })
+    }
+
+    /// How calls of `tool` are confirmed, `None` when they run right away
+    pub fn method(&self, tool: &str) -> Option<ConfirmMethod> {
+        self.tools.get(tool).copied()
+    }
+}
+
+/// Ask the MCP client to approve `message`, or `None` when it cannot be asked
+async fn elicit_confirmation(
+    peer: &Peer<RoleServer>,
+    message: String,
+) -> Result<Option<bool>, McpError> {
+    if !peer
+        .supported_elicitation_modes()
+        .contains(&ElicitationMode::Form)
+    {
+        return Ok(None);
+    }
+    let schema = ElicitationSchema::builder()
+        .required_property(
+            "approve",
+            PrimitiveSchema::Boolean(
+                BooleanSchema::new()
+                    .title("Approve")
+                    .description("Let the tool make the change described above")
+                    .with_default(false),
+            ),
+        )
+        .build()
+        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+    let result = peer
+        .create_elicitation_with_timeout(
+            CreateElicitationRequestParams::FormElicitationParams {
+                meta: None,
+                message,
+                requested_schema: schema,
+            },
+            Some(CONFIRMATION_TIMEOUT),
+        )
+        .await
+        .map_err(|e| {
+            McpError::internal_error(format!("Failed to ask for confirmation: {e}"), None)
+        })?;
+    let approved = result.action == ElicitationAction::Accept
+        && result
+            .content
+            .as_ref()
+            .and_then(|content| content.get("approve"))
+            .and_then(|approve| approve.as_bool())
+            .unwrap_or(false);
+    Ok(Some(approved))
+}
+
+impl NeovimMcpServer {
+    /// Ask the user to approve a call of `tool` before it changes anything,
+    /// when the confirmation policy requires it. `summary` describes the
+    /// change, e.g. the edits about to be applied.
+    pub async fn confirm_tool_call(
+        &self,
+        tool: &str,
+        client: &(dyn NeovimClientTrait + Send),
+        peer: &Peer<RoleServer>,
+        summary: &str,
+    ) -> Result<(), McpError> {
+        let Some(method) = self.confirmation_policy.method(tool) else {
+            return Ok(());
+        };
+        let target = client.target().unwrap_or_else(|| "Unknown".to_string());
+        let message = format!("Allow {tool} on {target}?\n\n{summary}");
+        debug!("Asking to confirm {tool} via {method:?}");
+
+        let elicited = match method {
+            ConfirmMethod::Elicit => elicit_confirmation(peer, message.clone()).await?,
+            ConfirmMethod::Neovim => None,
+        };
+        let approved = match elicited {
+            Some(approved) => approved,
+            None => {
+                client
+                    .ask_confirmation(&message, CONFIRMATION_TIMEOUT.as_millis() as u64)
+                    .await?
+            }
+        };
+        if approved {
+            Ok(())
+        } else {
+            Err(McpError::invalid_request(
+                format!("The user rejected {tool}, nothing was changed"),
+                None,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confirmation_policy_parse() {
+        let policy = ConfirmationPolicy::parse(&["lsp_rename", "exec_lua=nvim"]).unwrap();
+        assert_eq!(policy.method("lsp_rename"), Some(ConfirmMethod::Elicit));
+        assert_eq!(policy.method("exec_lua"), Some(ConfirmMethod::Neovim));
+        assert_eq!(policy.method("lsp_apply_edit"), None);
+
+        let policy = ConfirmationPolicy::parse(&["all=nvim", "lsp_rename=elicit"]).unwrap();
+        assert_eq!(policy.method("lsp_formatting"), Some(ConfirmMethod::Neovim));
+        assert_eq!(policy.method("lsp_rename"), Some(ConfirmMethod::Elicit));
+        assert_eq!(policy.method("read"), None);
+
+        assert!(ConfirmationPolicy::parse(&["read"]).is_err());
+        assert!(ConfirmationPolicy::parse(&["exec_lua=ask"]).is_err());
+    }
+}
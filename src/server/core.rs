@@ -1,16 +1,28 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::process::Command;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, PoisonError, RwLock, Weak};
+use std::time::Duration;
 
-use dashmap::DashMap;
-use rmcp::{ErrorData as McpError, RoleServer, service::RequestContext};
+use dashmap::{DashMap, DashSet};
+use rmcp::{
+    ErrorData as McpError, RoleServer,
+    handler::server::router::prompt::PromptRouter,
+    model::{LoggingLevel, Prompt, ResourceUpdatedNotificationParam},
+    service::{Peer, RequestContext},
+};
+use tokio::sync::{Mutex, Notify, broadcast::error::RecvError};
 use tracing::{debug, info, warn};
 
 use crate::{
-    neovim::{NeovimClientTrait, NeovimError},
+    neovim::{
+        EMBED_TARGET_PREFIX, InstanceInfo, NeovimClientConfig, NeovimClientTrait, NeovimError,
+        client::Notification,
+    },
     server::{
+        confirmation::ConfirmationPolicy,
         hybrid_router::{DynamicToolBox, HybridToolRouter},
-        lua_tools,
+        lua_tools::{self, LuaPromptConfig, LuaResourceConfig},
     },
 };
 
@@ -26,10 +38,33 @@ impl From<NeovimError> for McpError {
     }
 }
 
+#[derive(Clone)]
 pub struct NeovimMcpServer {
-    pub nvim_clients: Arc<DashMap<String, Box<dyn NeovimClientTrait + Send>>>,
+    pub nvim_clients: Arc<Connections>,
     pub hybrid_router: HybridToolRouter,
+    pub prompt_router: PromptRouter<NeovimMcpServer>,
+    /// Prompts registered by the Lua plugin, by connection ID then name
+    lua_prompts: Arc<DashMap<String, HashMap<String, LuaPromptConfig>>>,
+    /// Resources registered by the Lua plugin, by connection ID then URI
+    lua_resources: Arc<DashMap<String, HashMap<String, LuaResourceConfig>>>,
     pub connect_mode: Option<String>,
+    /// Client configuration for new connections, unless overridden when connecting
+    pub client_config: NeovimClientConfig,
+    /// Serializes reconnection of stale connections
+    reconnect_lock: Arc<Mutex<()>>,
+    /// Connections made by auto-connect, the only ones that may be rebound to
+    /// another instance of the project when stale
+    pub(crate) auto_connected: Arc<DashSet<String>>,
+    /// Connection used when a request omits `connection_id`
+    default_connection: Arc<RwLock<Option<String>>>,
+    /// Tracks tool calls and connections for graceful shutdown
+    shutdown: ShutdownCoordinator,
+    /// Resources the peer subscribed to
+    subscriptions: ResourceSubscriptions,
+    /// Minimum level of the log messages sent to the peer
+    pub(crate) log_level: Arc<RwLock<LoggingLevel>>,
+    /// Tools that ask the user for approval before changing anything
+    pub(crate) confirmation_policy: Arc<ConfirmationPolicy>,
 }
 
 impl NeovimMcpServer {
@@ -41,19 +76,82 @@ impl NeovimMcpServer {
         debug!("Creating new NeovimMcpServer instance");
         let static_router = crate::server::tools::build_tool_router();
         let static_tool_descriptions = Self::tool_descriptions();
+        let nvim_clients = Arc::new(DashMap::new());
+        let shutdown = ShutdownCoordinator::default();
+        shutdown.register(&nvim_clients);
         Self {
-            nvim_clients: Arc::new(DashMap::new()),
+            nvim_clients,
             hybrid_router: HybridToolRouter::new(static_router, static_tool_descriptions),
+            prompt_router: crate::server::prompts::build_prompt_router(),
+            lua_prompts: Arc::new(DashMap::new()),
+            lua_resources: Arc::new(DashMap::new()),
             connect_mode,
+            client_config: NeovimClientConfig::default(),
+            reconnect_lock: Arc::new(Mutex::new(())),
+            auto_connected: Arc::new(DashSet::new()),
+            default_connection: Arc::new(RwLock::new(None)),
+            shutdown,
+            subscriptions: ResourceSubscriptions::default(),
+            log_level: Arc::new(RwLock::new(crate::server::logging::DEFAULT_LOG_LEVEL)),
+            confirmation_policy: Arc::new(ConfirmationPolicy::default()),
         }
     }
 
+    /// Use `config` for connections that do not override it
+    pub fn with_client_config(mut self, config: NeovimClientConfig) -> Self {
+        self.client_config = config;
+        self
+    }
+
+    /// Ask the user before running the tools `policy` requires confirmation for
+    pub fn with_confirmation_policy(mut self, policy: ConfirmationPolicy) -> Self {
+        self.confirmation_policy = Arc::new(policy);
+        self
+    }
+
+    /// Share `shutdown` with other servers, so that it drains their tool
+    /// calls and closes their connections too
+    pub fn with_shutdown_coordinator(mut self, shutdown: ShutdownCoordinator) -> Self {
+        shutdown.register(&self.nvim_clients);
+        self.shutdown = shutdown;
+        self
+    }
+
+    pub fn shutdown_coordinator(&self) -> &ShutdownCoordinator {
+        &self.shutdown
+    }
+
+    pub fn log_level(&self) -> LoggingLevel {
+        *self
+            .log_level
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn set_log_level(&self, level: LoggingLevel) {
+        *self
+            .log_level
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = level;
+        crate::server::logging::update_log_filter();
+    }
+
     pub fn router(&self) -> &HybridToolRouter {
         &self.hybrid_router
     }
 
     /// Generate shorter connection ID with collision detection
     pub fn generate_shorter_connection_id(&self, target: &str) -> String {
+        // Same target, return the existing connection ID (connection replacement).
+        // Reconnected instances keep their original ID, which need not match the hash
+        if let Some(existing) = self
+            .nvim_clients
+            .iter()
+            .find(|entry| entry.value().target().as_deref() == Some(target))
+        {
+            return existing.key().clone();
+        }
+
         let full_hash = b3sum(target);
         let id_length = 7;
 
@@ -61,33 +159,24 @@ impl NeovimMcpServer {
         for start in 0..=(full_hash.len().saturating_sub(id_length)) {
             let candidate = &full_hash[start..start + id_length];
 
-            if let Some(existing_client) = self.nvim_clients.get(candidate) {
-                // Check if the existing connection has the same target
-                if let Some(existing_target) = existing_client.target()
-                    && existing_target == target
-                {
-                    // Same target, return existing connection ID (connection replacement)
-                    return candidate.to_string();
-                }
-                // Different target, continue looking for another ID
-                continue;
+            // Taken by a different target, keep looking for another ID
+            if !self.nvim_clients.contains_key(candidate) {
+                return candidate.to_string();
             }
-
-            // No existing connection with this ID, safe to use
-            return candidate.to_string();
         }
 
         // Fallback to full hash if somehow all combinations are taken
         full_hash
     }
 
-    /// Get connection by ID with proper error handling
+    /// Get a connection by ID, or the default connection when no ID is given
     pub fn get_connection(
         &'_ self,
-        connection_id: &str,
+        connection_id: Option<&str>,
     ) -> Result<dashmap::mapref::one::Ref<'_, String, Box<dyn NeovimClientTrait + Send>>, McpError>
     {
-        self.nvim_clients.get(connection_id).ok_or_else(|| {
+        let connection_id = self.resolve_connection_id(connection_id)?;
+        self.nvim_clients.get(&connection_id).ok_or_else(|| {
             McpError::invalid_request(
                 format!("No Neovim connection found for ID: {connection_id}"),
                 None,
@@ -95,6 +184,61 @@ impl NeovimMcpServer {
         })
     }
 
+    /// Resolve an optional connection ID, falling back to the default connection
+    pub fn resolve_connection_id(&self, connection_id: Option<&str>) -> Result<String, McpError> {
+        if let Some(connection_id) = connection_id {
+            return Ok(connection_id.to_string());
+        }
+        self.default_connection_id().ok_or_else(|| {
+            let mut connection_ids: Vec<String> = self
+                .nvim_clients
+                .iter()
+                .map(|entry| entry.key().clone())
+                .collect();
+            connection_ids.sort();
+            let message = if connection_ids.is_empty() {
+                "No Neovim connection, use get_targets and connect first".to_string()
+            } else {
+                format!(
+                    "connection_id is required: several connections are active ({}) and no default is set, use set_default_connection to choose one",
+                    connection_ids.join(", ")
+                )
+            };
+            McpError::invalid_params(message, None)
+        })
+    }
+
+    /// The connection used when a request omits `connection_id`: the one set
+    /// with `set_default_connection`, or the only active connection
+    pub fn default_connection_id(&self) -> Option<String> {
+        let explicit = self
+            .default_connection
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        if let Some(connection_id) = explicit
+            && self.nvim_clients.contains_key(&connection_id)
+        {
+            return Some(connection_id);
+        }
+        if self.nvim_clients.len() == 1 {
+            return self
+                .nvim_clients
+                .iter()
+                .next()
+                .map(|entry| entry.key().clone());
+        }
+        None
+    }
+
+    /// Set or clear the connection used when a request omits `connection_id`
+    pub fn set_default_connection_id(&self, connection_id: Option<String>) {
+        *self
+            .default_connection
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = connection_id;
+    }
+
     /// Get dynamic connections info for LLM
     pub fn get_connections_instruction(&self) -> String {
         let mut instructions = String::from("## Connection Status\n\n");
@@ -106,6 +250,7 @@ impl NeovimMcpServer {
         }
 
         // Show active connections with their IDs
+        let default_connection_id = self.default_connection_id();
         let connections: Vec<_> = self
             .nvim_clients
             .iter()
@@ -115,9 +260,19 @@ impl NeovimMcpServer {
                     .value()
                     .target()
                     .unwrap_or_else(|| "Unknown".to_string());
+                let stale = if entry.value().is_stale() {
+                    " (stale: Neovim instance exited)"
+                } else {
+                    ""
+                };
+                let default = if default_connection_id.as_ref() == Some(connection_id) {
+                    " (default)"
+                } else {
+                    ""
+                };
                 format!(
-                    "- **Connection ID: `{}`** → Target: `{}`",
-                    connection_id, target
+                    "- **Connection ID: `{}`** → Target: `{}`{}{}",
+                    connection_id, target, default, stale
                 )
             })
             .collect();
@@ -130,6 +285,10 @@ impl NeovimMcpServer {
                 instructions.push_str(&format!("{}\n", connection));
             }
             instructions.push_str("\n**Ready to use!** You can immediately use any connection-aware tools with the connection IDs above.");
+            if default_connection_id.is_some() {
+                instructions
+                    .push_str(" `connection_id` can be omitted to use the default connection.");
+            }
         }
 
         instructions
@@ -145,9 +304,87 @@ impl NeovimMcpServer {
             .register_dynamic_tool(connection_id, tool)
     }
 
-    /// Remove all dynamic tools for a connection
+    /// Remove all dynamic tools, Lua prompts and Lua resources for a connection
     pub fn unregister_dynamic_tools(&self, connection_id: &str) {
-        self.hybrid_router.unregister_dynamic_tools(connection_id)
+        self.hybrid_router.unregister_dynamic_tools(connection_id);
+        self.lua_prompts.remove(connection_id);
+        self.lua_resources.remove(connection_id);
+    }
+
+    /// Replace the Lua prompts of a connection. Prompts named like a
+    /// built-in prompt are ignored.
+    pub fn register_lua_prompts(
+        &self,
+        connection_id: &str,
+        mut prompts: HashMap<String, LuaPromptConfig>,
+    ) {
+        prompts.retain(|name, _| {
+            let builtin = self.prompt_router.has_route(name);
+            if builtin {
+                warn!("Lua prompt '{name}' of connection '{connection_id}' shadows a built-in prompt, ignoring it");
+            }
+            !builtin
+        });
+        self.lua_prompts.insert(connection_id.to_string(), prompts);
+    }
+
+    /// Replace the Lua resources of a connection
+    pub fn register_lua_resources(
+        &self,
+        connection_id: &str,
+        resources: HashMap<String, LuaResourceConfig>,
+    ) {
+        self.lua_resources
+            .insert(connection_id.to_string(), resources);
+    }
+
+    /// Lua prompts of all connections, once per name
+    pub fn list_lua_prompts(&self) -> Vec<Prompt> {
+        let mut prompts = BTreeMap::new();
+        for entry in self.lua_prompts.iter() {
+            for (name, prompt) in entry.value() {
+                prompts
+                    .entry(name.clone())
+                    .or_insert_with(|| prompt.to_prompt());
+            }
+        }
+        prompts.into_values().collect()
+    }
+
+    /// Lua prompt `name` of a connection
+    pub fn get_lua_prompt(&self, connection_id: &str, name: &str) -> Option<LuaPromptConfig> {
+        self.lua_prompts
+            .get(connection_id)
+            .and_then(|prompts| prompts.get(name).cloned())
+    }
+
+    /// Lua resources of all connections, once per URI
+    pub fn list_lua_resources(&self) -> Vec<LuaResourceConfig> {
+        let mut resources = BTreeMap::new();
+        for entry in self.lua_resources.iter() {
+            for (uri, resource) in entry.value() {
+                resources
+                    .entry(uri.clone())
+                    .or_insert_with(|| resource.clone());
+            }
+        }
+        resources.into_values().collect()
+    }
+
+    /// Connection serving Lua resource `uri`, preferring the default connection
+    pub fn lua_resource_connection(&self, uri: &str) -> Option<String> {
+        if let Some(connection_id) = self.default_connection_id()
+            && self
+                .lua_resources
+                .get(&connection_id)
+                .is_some_and(|resources| resources.contains_key(uri))
+        {
+            return Some(connection_id);
+        }
+        self.lua_resources
+            .iter()
+            .find(|entry| entry.value().contains_key(uri))
+            .map(|entry| entry.key().clone())
     }
 
     /// Get count of dynamic tools for a connection
@@ -164,6 +401,49 @@ impl NeovimMcpServer {
         Ok(())
     }
 
+    /// Check that a connection is still alive before using it.
+    ///
+    /// A stale connection is reconnected to its previous target, or when it
+    /// was made by auto-connect to another running Neovim instance of the
+    /// current project, under the same connection ID. Returns whether a reconnection
+    /// happened.
+    pub async fn ensure_connection_alive(&self, connection_id: &str) -> Result<bool, McpError> {
+        if !self
+            .nvim_clients
+            .get(connection_id)
+            .is_some_and(|client| client.is_stale())
+        {
+            return Ok(false);
+        }
+
+        let _guard = self.reconnect_lock.lock().await;
+        // Another request may have reconnected while we waited for the lock
+        let Some(target) = self
+            .nvim_clients
+            .get(connection_id)
+            .filter(|client| client.is_stale())
+            .map(|client| client.target().unwrap_or_default())
+        else {
+            return Ok(false);
+        };
+        warn!("Connection '{connection_id}' to {target} is stale, the Neovim instance exited");
+        if target.starts_with(EMBED_TARGET_PREFIX) {
+            return Err(McpError::invalid_request(
+                format!(
+                    "Embedded Neovim of connection '{connection_id}' exited, disconnect it and spawn a new one"
+                ),
+                None,
+            ));
+        }
+
+        let rediscover = self.connect_mode.as_deref() == Some("auto")
+            && self.auto_connected.contains(connection_id);
+        let new_target =
+            reconnect_stale_connection(self, connection_id, &target, rediscover).await?;
+        info!("Reconnected '{connection_id}' from {target} to {new_target}");
+        Ok(true)
+    }
+
     pub(crate) async fn setup_new_client(
         &self,
         connection_id: &String,
@@ -171,6 +451,8 @@ impl NeovimMcpServer {
         ctx: &RequestContext<RoleServer>,
     ) -> Result<(), McpError> {
         client.setup_autocmd().await?;
+        // Explicitly made connections replace auto-connected ones of the same ID
+        self.auto_connected.remove(connection_id);
 
         let mut should_notify = self.nvim_clients.is_empty();
 
@@ -206,6 +488,46 @@ impl NeovimMcpServer {
     }
 }
 
+impl NeovimMcpServer {
+    /// Subscribe `peer` to updates of `uri`, watching its connection for changes
+    pub fn subscribe_resource(&self, uri: &str, peer: Peer<RoleServer>) -> Result<(), McpError> {
+        let Some(connection_id) = subscribable_connection_id(uri) else {
+            return Err(McpError::invalid_params(
+                format!("Resource '{uri}' does not support subscriptions"),
+                None,
+            ));
+        };
+        if !self.nvim_clients.contains_key(connection_id) {
+            return Err(McpError::invalid_params(
+                format!("No Neovim connection found for ID: {connection_id}"),
+                None,
+            ));
+        }
+
+        *self
+            .subscriptions
+            .peer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(peer);
+        self.subscriptions.uris.insert(uri.to_string());
+        if self
+            .subscriptions
+            .watchers
+            .insert(connection_id.to_string())
+        {
+            tokio::spawn(watch_resource_updates(
+                self.clone(),
+                connection_id.to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn unsubscribe_resource(&self, uri: &str) {
+        self.subscriptions.uris.remove(uri);
+    }
+}
+
 impl Default for NeovimMcpServer {
     fn default() -> Self {
         Self::new()
@@ -258,21 +580,51 @@ fn get_socket_search_dirs() -> BTreeSet<String> {
     BTreeSet::from([get_socket_dir(), "/tmp".to_string()])
 }
 
-/// Find all existing nvim-mcp socket targets in the filesystem
-/// Returns a vector of socket paths that match the pattern generated by the Lua plugin
-pub fn find_get_all_targets() -> Vec<String> {
-    let mut targets = Vec::new();
+/// Find socket files matching `file_pattern` in all socket search dirs
+fn find_sockets(file_pattern: &str) -> Vec<String> {
+    let mut sockets = Vec::new();
     for dir in get_socket_search_dirs() {
-        let pattern = format!("{dir}/nvim-mcp.*.sock");
-        if let Ok(paths) = glob::glob(&pattern) {
-            targets.extend(
-                paths
-                    .filter_map(|entry| entry.ok())
-                    .map(|path| path.to_string_lossy().to_string()),
-            );
+        let pattern = format!("{dir}/{file_pattern}");
+        match glob::glob(&pattern) {
+            Ok(paths) => {
+                sockets.extend(
+                    paths
+                        .filter_map(|entry| entry.ok())
+                        .map(|path| path.to_string_lossy().to_string()),
+                );
+            }
+            Err(e) => {
+                warn!(
+                    "Glob error while searching for Neovim sockets with pattern '{}': {}",
+                    pattern, e
+                );
+            }
         }
     }
-    targets
+    sockets
+}
+
+/// Keep only sockets that belong to a running Neovim instance
+async fn filter_live_targets(sockets: Vec<String>) -> Vec<String> {
+    let alive =
+        futures::future::join_all(sockets.iter().map(|socket| is_target_alive(socket))).await;
+    sockets
+        .into_iter()
+        .zip(alive)
+        .filter_map(|(socket, alive)| {
+            if !alive {
+                debug!("Skipping stale socket {socket}");
+            }
+            alive.then_some(socket)
+        })
+        .collect()
+}
+
+/// Find all existing nvim-mcp socket targets in the filesystem
+/// Returns socket paths matching the pattern generated by the Lua plugin whose
+/// Neovim instance is still running
+pub async fn find_get_all_targets() -> Vec<String> {
+    filter_live_targets(find_sockets("nvim-mcp.*.sock")).await
 }
 
 /// Get current project root directory
@@ -301,30 +653,216 @@ fn escape_path(path: &str) -> String {
 
 /// Find nvim-mcp socket targets for the current project only
 /// Returns sockets that match the current project's escaped path
-pub fn find_targets_for_current_project() -> Vec<String> {
+pub async fn find_targets_for_current_project() -> Vec<String> {
     let current_project_root = get_current_project_root();
     let escaped_project_root = escape_path(&current_project_root);
 
-    let mut targets = Vec::new();
-    for dir in get_socket_search_dirs() {
-        let pattern = format!("{dir}/nvim-mcp.{escaped_project_root}.*.sock");
-        match glob::glob(&pattern) {
-            Ok(paths) => {
-                targets.extend(
-                    paths
-                        .filter_map(|entry| entry.ok())
-                        .map(|path| path.to_string_lossy().to_string()),
-                );
-            }
-            Err(e) => {
-                warn!(
-                    "Glob error while searching for Neovim sockets with pattern '{}': {}",
-                    pattern, e
-                );
+    filter_live_targets(find_sockets(&format!(
+        "nvim-mcp.{escaped_project_root}.*.sock"
+    )))
+    .await
+}
+
+/// Project root and PID encoded in a socket name created by the Lua plugin,
+/// i.e. `nvim-mcp.<escaped project root>.<pid>.sock`
+pub fn parse_socket_name(target: &str) -> Option<(String, u32)> {
+    let file_name = std::path::Path::new(target).file_name()?.to_str()?;
+    let stem = file_name.strip_prefix("nvim-mcp.")?.strip_suffix(".sock")?;
+    let (escaped_project_root, pid) = stem.rsplit_once('.')?;
+    Some((escaped_project_root.replace('%', "/"), pid.parse().ok()?))
+}
+
+/// Whether a process with the given PID is still running
+pub fn is_pid_alive(pid: u32) -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        std::path::Path::new(&format!("/proc/{pid}")).exists()
+    }
+    #[cfg(all(unix, not(target_os = "linux")))]
+    {
+        // SAFETY: signal 0 only checks whether the process exists. A process
+        // of another user that may not be signalled is still running.
+        unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+        || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        true
+    }
+}
+
+/// How long a socket may take to accept a connection before it counts as stale
+const SOCKET_PROBE_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Whether a socket belongs to a running Neovim instance: the PID in its name
+/// must still be alive and the socket must accept connections
+pub async fn is_target_alive(target: &str) -> bool {
+    if let Some((_, pid)) = parse_socket_name(target)
+        && !is_pid_alive(pid)
+    {
+        return false;
+    }
+    #[cfg(unix)]
+    {
+        tokio::time::timeout(
+            SOCKET_PROBE_TIMEOUT,
+            tokio::net::UnixStream::connect(target),
+        )
+        .await
+        .is_ok_and(|stream| stream.is_ok())
+    }
+    #[cfg(not(unix))]
+    {
+        true
+    }
+}
+
+/// Remove socket files left behind by exited Neovim instances.
+/// Only sockets owned by the current user are removed. Returns the removed paths.
+pub async fn cleanup_stale_sockets() -> Vec<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        // SAFETY: getuid has no preconditions and cannot fail
+        let uid = unsafe { libc::getuid() };
+        let mut stale = Vec::new();
+        for socket in find_sockets("nvim-mcp.*.sock") {
+            if std::fs::symlink_metadata(&socket).is_ok_and(|metadata| metadata.uid() == uid)
+                && !is_target_alive(&socket).await
+            {
+                stale.push(socket);
             }
         }
+        stale
+            .into_iter()
+            .filter(|socket| match std::fs::remove_file(socket) {
+                Ok(()) => {
+                    info!("Removed stale socket {socket}");
+                    true
+                }
+                Err(e) => {
+                    warn!("Failed to remove stale socket {socket}: {e}");
+                    false
+                }
+            })
+            .collect()
+    }
+    #[cfg(not(unix))]
+    {
+        Vec::new()
+    }
+}
+
+/// How long to wait for a Neovim instance to describe itself
+const TARGET_QUERY_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Metadata about a Neovim target, used to choose among several instances
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
+pub struct TargetInfo {
+    /// Connection ID, if the server is connected to this target
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Socket path or TCP address
+    pub target: String,
+    /// Project root the socket was created for
+    pub project_root: Option<String>,
+    /// Process ID of the Neovim instance
+    pub pid: Option<u32>,
+    /// Whether the Neovim instance behind the target no longer exists
+    pub stale: bool,
+    /// Neovim version, e.g. "0.11.3"
+    pub nvim_version: Option<String>,
+    /// Current working directory of the instance
+    pub cwd: Option<String>,
+    /// Name of the buffer in the current window
+    pub current_buffer: Option<String>,
+    /// Whether the nvim-mcp plugin is loaded
+    pub plugin_loaded: Option<bool>,
+    /// Client configuration of the connection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<NeovimClientConfig>,
+}
+
+impl TargetInfo {
+    fn new(target: &str, id: Option<String>) -> Self {
+        let (project_root, pid) = parse_socket_name(target).unzip();
+        Self {
+            id,
+            target: target.to_string(),
+            project_root,
+            pid,
+            stale: pid.is_some_and(|pid| !is_pid_alive(pid)),
+            nvim_version: None,
+            cwd: None,
+            current_buffer: None,
+            plugin_loaded: None,
+            config: None,
+        }
+    }
+
+    fn with_instance(self, info: InstanceInfo) -> Self {
+        Self {
+            pid: Some(info.pid),
+            nvim_version: Some(info.nvim_version),
+            cwd: Some(info.cwd),
+            current_buffer: Some(info.current_buffer),
+            plugin_loaded: Some(info.plugin_loaded),
+            ..self
+        }
+    }
+}
+
+/// Describe an active connection, querying its Neovim instance
+pub async fn describe_connection(server: &NeovimMcpServer, connection_id: &str) -> TargetInfo {
+    let Some(client) = server.nvim_clients.get(connection_id) else {
+        return TargetInfo::new("Unknown", Some(connection_id.to_string()));
+    };
+    let target = client.target().unwrap_or_else(|| "Unknown".to_string());
+    let mut info = TargetInfo::new(&target, Some(connection_id.to_string()));
+    info.stale |= client.is_stale();
+    info.config = Some(client.config().clone());
+    if info.stale {
+        return info;
+    }
+    match tokio::time::timeout(TARGET_QUERY_TIMEOUT, client.get_instance_info()).await {
+        Ok(Ok(instance)) => info.with_instance(instance),
+        _ => {
+            debug!("Failed to query instance info for connection '{connection_id}'");
+            info
+        }
+    }
+}
+
+/// Describe a target, through its connection if there is one, otherwise with
+/// a single request over a bare RPC connection
+pub async fn describe_target(server: &NeovimMcpServer, target: &str) -> TargetInfo {
+    let connection_id = server
+        .nvim_clients
+        .iter()
+        .find(|entry| entry.value().target().as_deref() == Some(target))
+        .map(|entry| entry.key().clone());
+    if let Some(connection_id) = connection_id {
+        return describe_connection(server, &connection_id).await;
+    }
+
+    let info = TargetInfo::new(target, None);
+    if info.stale {
+        return info;
+    }
+    let instance = tokio::time::timeout(
+        TARGET_QUERY_TIMEOUT,
+        crate::neovim::client::query_instance_info(target),
+    )
+    .await;
+    match instance {
+        Ok(Ok(instance)) => info.with_instance(instance),
+        _ => {
+            debug!("Failed to query instance info for {target}");
+            info
+        }
     }
-    targets
 }
 
 /// Connect to a single target and return the connection ID
@@ -349,7 +887,8 @@ pub async fn auto_connect_single_target(
     }
 
     // Import NeovimClient here to avoid circular imports
-    let mut client = crate::neovim::NeovimClient::default();
+    let mut client =
+        crate::neovim::NeovimClient::default().with_config(server.client_config.clone());
     client.connect_path(target).await?;
     client.setup_autocmd().await?;
 
@@ -360,12 +899,262 @@ pub async fn auto_connect_single_target(
     Ok(connection_id)
 }
 
+/// Socket or named pipe path for a spawned Neovim instance. It does not match
+/// the plugin's naming, so discovery never picks it up.
+fn spawned_socket_path() -> String {
+    static SPAWN_COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+    let index = SPAWN_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let server_pid = std::process::id();
+    if cfg!(windows) {
+        format!("\\\\.\\pipe\\nvim-mcp-spawned.{server_pid}.{index}")
+    } else {
+        format!(
+            "{}/nvim-mcp-spawned.{server_pid}.{index}.sock",
+            get_socket_dir()
+        )
+    }
+}
+
+/// Start a headless Neovim instance in `cwd`, defaulting to the server's
+/// working directory, and connect to it. With `embed` the instance is driven
+/// over its stdin/stdout instead of a socket. Returns the connection ID, the
+/// client and the PID. The instance is stopped when the client disconnects.
+pub async fn spawn_nvim(
+    server: &NeovimMcpServer,
+    cwd: Option<&str>,
+    init_file: Option<&str>,
+    embed: bool,
+    config: NeovimClientConfig,
+) -> Result<(String, Box<dyn NeovimClientTrait + Send + Sync>, u32), NeovimError> {
+    let cwd = match cwd {
+        Some(cwd) => std::path::PathBuf::from(cwd),
+        None => std::env::current_dir().map_err(|e| {
+            NeovimError::Connection(format!("Failed to get current working directory: {e}"))
+        })?,
+    };
+    if !cwd.is_dir() {
+        return Err(NeovimError::Connection(format!(
+            "Working directory does not exist: {}",
+            cwd.display()
+        )));
+    }
+
+    if embed {
+        let mut client = crate::neovim::NeovimClient::default().with_config(config);
+        let pid = client.connect_embed(&cwd, init_file).await?;
+        let target = client.target().unwrap_or_default();
+        let connection_id = server.generate_shorter_connection_id(&target);
+        return Ok((connection_id, Box::new(client), pid));
+    }
+
+    let listen = spawned_socket_path();
+    let mut client = crate::neovim::NeovimClient::default().with_config(config);
+    let pid = client.spawn_headless(&listen, &cwd, init_file).await?;
+    let connection_id = server.generate_shorter_connection_id(&listen);
+    Ok((connection_id, Box::new(client), pid))
+}
+
+/// Connections of a server, keyed by connection ID
+pub type Connections = DashMap<String, Box<dyn NeovimClientTrait + Send>>;
+
+/// How long to wait for a Neovim instance to remove the server's autocmds
+const TEARDOWN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Coordinates shutdown across the servers that share it, one per HTTP
+/// session. Tracks tool calls in flight so they can be drained, and the
+/// connections of every live server so they can be closed.
+#[derive(Clone, Default)]
+pub struct ShutdownCoordinator {
+    in_flight: Arc<AtomicUsize>,
+    idle: Arc<Notify>,
+    shutting_down: Arc<AtomicBool>,
+    connections: Arc<std::sync::Mutex<Vec<Weak<Connections>>>>,
+}
+
+/// Marks a tool call in flight until dropped
+pub struct CallGuard {
+    coordinator: ShutdownCoordinator,
+}
+
+impl Drop for CallGuard {
+    fn drop(&mut self) {
+        if self.coordinator.in_flight.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.coordinator.idle.notify_waiters();
+        }
+    }
+}
+
+impl ShutdownCoordinator {
+    fn register(&self, connections: &Arc<Connections>) {
+        let mut registered = self
+            .connections
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        registered.retain(|connections| connections.strong_count() > 0);
+        registered.push(Arc::downgrade(connections));
+    }
+
+    /// Track a tool call, or `None` once shutdown has started
+    pub fn begin_call(&self) -> Option<CallGuard> {
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        let guard = CallGuard {
+            coordinator: self.clone(),
+        };
+        if self.shutting_down.load(Ordering::Acquire) {
+            return None;
+        }
+        Some(guard)
+    }
+
+    /// Refuse new tool calls and wait up to `timeout` for the ones in flight.
+    /// Returns whether all calls finished.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        self.shutting_down.store(true, Ordering::Release);
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let idle = self.idle.notified();
+            let in_flight = self.in_flight.load(Ordering::Acquire);
+            if in_flight == 0 {
+                return true;
+            }
+            debug!("Waiting for {in_flight} tool calls to finish");
+            if tokio::time::timeout_at(deadline, idle).await.is_err() {
+                warn!("Shutting down with {in_flight} tool calls still running");
+                return false;
+            }
+        }
+    }
+
+    /// Drain tool calls in flight, then remove the server's autocmds from
+    /// every connected Neovim instance and disconnect, killing the instances
+    /// the servers spawned
+    pub async fn shutdown(&self, timeout: Duration) {
+        self.drain(timeout).await;
+        let registered: Vec<Arc<Connections>> = self
+            .connections
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect();
+        for connections in registered {
+            close_connections(&connections).await;
+        }
+    }
+}
+
+async fn close_connections(connections: &Connections) {
+    let connection_ids: Vec<String> = connections
+        .iter()
+        .map(|entry| entry.key().clone())
+        .collect();
+    for connection_id in connection_ids {
+        if let Some((_, mut client)) = connections.remove(&connection_id) {
+            if !client.is_stale() {
+                match tokio::time::timeout(TEARDOWN_TIMEOUT, client.teardown_autocmd()).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => debug!("Failed to remove autocmd of '{connection_id}': {e}"),
+                    Err(_) => debug!("Timed out removing autocmd of '{connection_id}'"),
+                }
+            }
+            let _ = client.disconnect().await;
+            debug!("Disconnected '{connection_id}'");
+        }
+    }
+}
+
+/// Connect to a unix socket, named pipe or TCP target
+async fn connect_target(
+    target: &str,
+    config: NeovimClientConfig,
+) -> Result<Box<dyn NeovimClientTrait + Send>, NeovimError> {
+    if target.parse::<std::net::SocketAddr>().is_ok() {
+        let mut client = crate::neovim::NeovimClient::default().with_config(config);
+        client.connect_tcp(target).await?;
+        Ok(Box::new(client))
+    } else {
+        let mut client = crate::neovim::NeovimClient::default().with_config(config);
+        client.connect_path(target).await?;
+        Ok(Box::new(client))
+    }
+}
+
+/// Replace a stale connection, keeping its connection ID. The old target is
+/// tried first, then with `rediscover` the current project's sockets that no
+/// other connection refers to.
+pub async fn reconnect_stale_connection(
+    server: &NeovimMcpServer,
+    connection_id: &str,
+    old_target: &str,
+    rediscover: bool,
+) -> Result<String, NeovimError> {
+    let in_use: Vec<String> = server
+        .nvim_clients
+        .iter()
+        .filter(|entry| entry.key() != connection_id)
+        .filter_map(|entry| entry.value().target())
+        .collect();
+
+    // Keep the configuration the connection was made with
+    let config = server
+        .nvim_clients
+        .get(connection_id)
+        .map(|client| client.config().clone())
+        .unwrap_or_else(|| server.client_config.clone());
+
+    let mut candidates = vec![old_target.to_string()];
+    if rediscover {
+        candidates.extend(
+            find_targets_for_current_project()
+                .await
+                .into_iter()
+                .filter(|target| target != old_target),
+        );
+    }
+
+    for target in candidates
+        .into_iter()
+        .filter(|target| !in_use.contains(target))
+    {
+        let mut client = match connect_target(&target, config.clone()).await {
+            Ok(client) => client,
+            Err(e) => {
+                debug!("Reconnect candidate {target} unavailable: {e}");
+                continue;
+            }
+        };
+        if let Err(e) = client.setup_autocmd().await {
+            debug!("Failed to set up autocmd on {target}: {e}");
+            let _ = client.disconnect().await;
+            continue;
+        }
+
+        server.unregister_dynamic_tools(connection_id);
+        if let Err(e) =
+            lua_tools::discover_and_register_lua_tools(server, connection_id, client.as_ref()).await
+        {
+            warn!("Failed to discover Lua tools for connection '{connection_id}': {e}");
+        }
+        if let Some((_, mut old_client)) = server.nvim_clients.remove(connection_id) {
+            let _ = old_client.disconnect().await;
+        }
+        server
+            .nvim_clients
+            .insert(connection_id.to_string(), client);
+        return Ok(target);
+    }
+
+    Err(NeovimError::Connection(format!(
+        "Connection {connection_id} to {old_target} is stale because the Neovim instance exited, and no running instance was found to reconnect to"
+    )))
+}
+
 /// Auto-connect to all Neovim targets for the current project
 /// Returns list of successful connection IDs, or list of failures
 pub async fn auto_connect_current_project_targets(
     server: &NeovimMcpServer,
 ) -> Result<Vec<String>, Vec<(String, String)>> {
-    let project_targets = find_targets_for_current_project();
+    let project_targets = find_targets_for_current_project().await;
     let current_project = get_current_project_root();
 
     if project_targets.is_empty() {
@@ -384,6 +1173,7 @@ pub async fn auto_connect_current_project_targets(
     for target in project_targets {
         match auto_connect_single_target(server, &target).await {
             Ok(connection_id) => {
+                server.auto_connected.insert(connection_id.clone());
                 successful_connections.push(connection_id);
                 info!("Auto-connected to project Neovim instance: {target}");
             }
@@ -400,3 +1190,334 @@ pub async fn auto_connect_current_project_targets(
         Ok(successful_connections)
     }
 }
+
+/// How often the socket watcher rescans the socket directories
+const SOCKET_WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Changes to a subscribed resource within this window of the first one are
+/// reported with a single update notification
+const RESOURCE_UPDATE_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Resource URIs the peer subscribed to, and the connections being watched
+/// for changes to them
+#[derive(Clone, Default)]
+struct ResourceSubscriptions {
+    uris: Arc<DashSet<String>>,
+    peer: Arc<std::sync::Mutex<Option<Peer<RoleServer>>>>,
+    watchers: Arc<DashSet<String>>,
+}
+
+impl ResourceSubscriptions {
+    fn has_connection(&self, connection_id: &str) -> bool {
+        self.uris
+            .iter()
+            .any(|uri| subscribable_connection_id(&uri) == Some(connection_id))
+    }
+
+    fn peer(&self) -> Option<Peer<RoleServer>> {
+        self.peer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+/// Connection ID of a resource URI that can be subscribed to
+fn subscribable_connection_id(uri: &str) -> Option<&str> {
+    uri.strip_prefix("nvim-diagnostics://")
+        .or_else(|| uri.strip_prefix("nvim-buffer://"))?
+        .split('/')
+        .next()
+        .filter(|connection_id| !connection_id.is_empty())
+}
+
+/// Resource URIs whose content changed according to a Neovim notification
+fn updated_resource_uris(connection_id: &str, notification: &Notification) -> Vec<String> {
+    let buffer_id = notification
+        .args
+        .first()
+        .and_then(|arg| arg.as_map())
+        .and_then(|map| map.iter().find(|(key, _)| key.as_str() == Some("buf")))
+        .and_then(|(_, value)| value.as_u64());
+    match (notification.name.as_str(), buffer_id) {
+        ("NVIM_MCP_DiagnosticsChanged", Some(buffer_id)) => vec![
+            format!("nvim-diagnostics://{connection_id}/workspace"),
+            format!("nvim-diagnostics://{connection_id}/buffer/{buffer_id}"),
+        ],
+        ("NVIM_MCP_BufferChanged", Some(buffer_id)) => {
+            vec![format!("nvim-buffer://{connection_id}/{buffer_id}")]
+        }
+        _ => vec![],
+    }
+}
+
+/// Notify the peer of changes to the subscribed resources of a connection,
+/// until the connection is gone or none of its resources are subscribed
+async fn watch_resource_updates(server: NeovimMcpServer, connection_id: String) {
+    debug!("Watching resource updates of connection '{connection_id}'");
+    let subscriptions = &server.subscriptions;
+    loop {
+        forward_resource_updates(&server, &connection_id).await;
+        subscriptions.watchers.remove(&connection_id);
+
+        let connected = server
+            .nvim_clients
+            .get(&connection_id)
+            .is_some_and(|client| client.subscribe_notifications().is_some());
+        if !connected {
+            // Subscriptions end with their connection
+            subscriptions
+                .uris
+                .retain(|uri| subscribable_connection_id(uri) != Some(connection_id.as_str()));
+            break;
+        }
+        // Keep watching if a resource was subscribed to while stopping
+        if !subscriptions.has_connection(&connection_id)
+            || !subscriptions.watchers.insert(connection_id.clone())
+        {
+            break;
+        }
+    }
+    debug!("Stopped watching resource updates of connection '{connection_id}'");
+}
+
+async fn forward_resource_updates(server: &NeovimMcpServer, connection_id: &str) {
+    let subscriptions = &server.subscriptions;
+    let mut pending = BTreeSet::new();
+    // Reconnecting replaces the client, so follow the notifications of the new one
+    while let Some(mut receiver) = server
+        .nvim_clients
+        .get(connection_id)
+        .and_then(|client| client.subscribe_notifications())
+    {
+        let mut deadline = None;
+        loop {
+            let received = match deadline {
+                None => Some(receiver.recv().await),
+                Some(deadline) => tokio::time::timeout_at(deadline, receiver.recv())
+                    .await
+                    .ok(),
+            };
+            match received {
+                Some(Ok(notification)) => pending.extend(
+                    updated_resource_uris(connection_id, &notification)
+                        .into_iter()
+                        .filter(|uri| subscriptions.uris.contains(uri)),
+                ),
+                // Notifications were dropped, any of the resources may have changed
+                Some(Err(RecvError::Lagged(_))) => {
+                    pending.extend(subscriptions.uris.iter().filter_map(|uri| {
+                        (subscribable_connection_id(&uri) == Some(connection_id))
+                            .then(|| uri.clone())
+                    }))
+                }
+                Some(Err(RecvError::Closed)) => break,
+                // The debounce window elapsed
+                None => {
+                    deadline = None;
+                    notify_resources_updated(subscriptions, &mut pending).await;
+                }
+            }
+
+            if !pending.is_empty() {
+                deadline
+                    .get_or_insert_with(|| tokio::time::Instant::now() + RESOURCE_UPDATE_DEBOUNCE);
+            } else if !subscriptions.has_connection(connection_id) {
+                return;
+            }
+        }
+        notify_resources_updated(subscriptions, &mut pending).await;
+    }
+}
+
+async fn notify_resources_updated(
+    subscriptions: &ResourceSubscriptions,
+    pending: &mut BTreeSet<String>,
+) {
+    let Some(peer) = subscriptions.peer() else {
+        pending.clear();
+        return;
+    };
+    for uri in std::mem::take(pending) {
+        if !subscriptions.uris.contains(&uri) {
+            continue;
+        }
+        debug!("Resource updated: {uri}");
+        peer.notify_resource_updated(ResourceUpdatedNotificationParam::new(uri))
+            .await
+            .unwrap_or_else(|e| warn!("Failed to notify resource updated: {}", e));
+    }
+}
+
+/// Watch the current project's sockets for as long as the peer is connected.
+///
+/// New Neovim instances are connected and their Lua tools registered, stale
+/// connections are reconnected or dropped once their instance is gone, and the
+/// peer is notified whenever the tool list changes.
+pub async fn watch_project_targets(server: NeovimMcpServer, peer: Peer<RoleServer>) {
+    info!("Watching Neovim sockets for the current project");
+    let mut interval = tokio::time::interval(SOCKET_WATCH_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    while !peer.is_transport_closed() {
+        interval.tick().await;
+        if sync_project_targets(&server).await {
+            peer.notify_tool_list_changed()
+                .await
+                .unwrap_or_else(|e| warn!("Failed to notify tool list changed: {}", e));
+        }
+    }
+    debug!("Peer disconnected, stopped watching Neovim sockets");
+}
+
+/// Reconcile connections with the current project's sockets, returning whether
+/// any connection was added, replaced or removed
+pub async fn sync_project_targets(server: &NeovimMcpServer) -> bool {
+    let mut changed = false;
+
+    // Vanished instances: revive stale connections, or drop them
+    let stale: Vec<String> = server
+        .nvim_clients
+        .iter()
+        .filter(|entry| entry.value().is_stale())
+        .map(|entry| entry.key().clone())
+        .collect();
+    for connection_id in stale {
+        match server.ensure_connection_alive(&connection_id).await {
+            Ok(reconnected) => changed |= reconnected,
+            Err(e) => {
+                info!("Dropping connection '{connection_id}': {}", e.message);
+                if let Some((_, mut client)) = server.nvim_clients.remove(&connection_id) {
+                    let _ = client.disconnect().await;
+                }
+                server.auto_connected.remove(&connection_id);
+                server.unregister_dynamic_tools(&connection_id);
+                changed = true;
+            }
+        }
+    }
+
+    // New instances: connect to sockets no connection points at yet
+    let connected: Vec<String> = server
+        .nvim_clients
+        .iter()
+        .filter_map(|entry| entry.value().target())
+        .collect();
+    for target in find_targets_for_current_project().await {
+        if connected.contains(&target) {
+            continue;
+        }
+        match auto_connect_single_target(server, &target).await {
+            Ok(connection_id) => {
+                info!("Connected to new Neovim instance {target} with ID {connection_id}");
+                server.auto_connected.insert(connection_id.clone());
+                if let Some(client) = server.nvim_clients.get(&connection_id)
+                    && let Err(e) = lua_tools::discover_and_register_lua_tools(
+                        server,
+                        &connection_id,
+                        client.as_ref(),
+                    )
+                    .await
+                {
+                    warn!("Failed to discover Lua tools for connection '{connection_id}': {e}");
+                }
+                changed = true;
+            }
+            Err(e) => debug!("Failed to connect to new socket {target}: {e}"),
+        }
+    }
+
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_socket_name() {
+        assert_eq!(
+            parse_socket_name("/tmp/nvim-mcp.%home%user%my.project.4242.sock"),
+            Some(("/home/user/my.project".to_string(), 4242))
+        );
+        assert_eq!(
+            parse_socket_name("nvim-mcp.%srv%app.1.sock"),
+            Some(("/srv/app".to_string(), 1))
+        );
+        assert_eq!(parse_socket_name("/tmp/nvim-mcp.%srv%app.sock"), None);
+        assert_eq!(parse_socket_name("/tmp/other.%srv%app.1.sock"), None);
+        assert_eq!(parse_socket_name("127.0.0.1:6666"), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_is_target_alive() {
+        let dir = tempfile::tempdir().unwrap();
+        let pid = std::process::id();
+
+        let live = dir.path().join(format!("nvim-mcp.%srv%app.{pid}.sock"));
+        let _listener = std::os::unix::net::UnixListener::bind(&live).unwrap();
+        assert!(is_target_alive(live.to_str().unwrap()).await);
+
+        // Socket file left behind without a listener
+        let orphaned = dir.path().join(format!("nvim-mcp.%srv%other.{pid}.sock"));
+        drop(std::os::unix::net::UnixListener::bind(&orphaned).unwrap());
+        assert!(!is_target_alive(orphaned.to_str().unwrap()).await);
+
+        // Listening socket whose PID no longer exists
+        let dead_pid = dir
+            .path()
+            .join(format!("nvim-mcp.%srv%app.{}.sock", u32::MAX));
+        let _dead_listener = std::os::unix::net::UnixListener::bind(&dead_pid).unwrap();
+        assert!(!is_target_alive(dead_pid.to_str().unwrap()).await);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drains_calls() {
+        let shutdown = ShutdownCoordinator::default();
+        let call = shutdown.begin_call().unwrap();
+
+        // Calls in flight hold up shutdown until the deadline
+        assert!(!shutdown.drain(Duration::from_millis(50)).await);
+        assert!(shutdown.begin_call().is_none());
+
+        let drained = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.drain(Duration::from_secs(5)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        drop(call);
+        assert!(drained.await.unwrap());
+    }
+
+    #[test]
+    fn test_updated_resource_uris() {
+        let buffer = rmpv::Value::Map(vec![("buf".into(), 3.into())]);
+        let notification = |name: &str| Notification {
+            name: name.to_string(),
+            args: vec![buffer.clone()],
+            timestamp: std::time::SystemTime::now(),
+        };
+
+        assert_eq!(
+            updated_resource_uris("abc1234", &notification("NVIM_MCP_DiagnosticsChanged")),
+            vec![
+                "nvim-diagnostics://abc1234/workspace",
+                "nvim-diagnostics://abc1234/buffer/3"
+            ]
+        );
+        assert_eq!(
+            updated_resource_uris("abc1234", &notification("NVIM_MCP_BufferChanged")),
+            vec!["nvim-buffer://abc1234/3"]
+        );
+        assert!(updated_resource_uris("abc1234", &notification("NVIM_MCP_LspAttach")).is_empty());
+
+        for uri in updated_resource_uris("abc1234", &notification("NVIM_MCP_DiagnosticsChanged")) {
+            assert_eq!(subscribable_connection_id(&uri), Some("abc1234"));
+        }
+        assert_eq!(subscribable_connection_id("nvim-connections://"), None);
+        assert_eq!(
+            subscribable_connection_id("nvim-diagnostics:///workspace"),
+            None
+        );
+    }
+}
@@ -1,11 +1,14 @@
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
 
 use dashmap::DashMap;
 use rmcp::{
     ErrorData as McpError,
     handler::server::{router::tool::ToolRouter, tool::ToolCallContext},
-    model::{CallToolRequestParams, CallToolResult, Tool, ToolAnnotations},
+    model::{
+        CallToolRequestParams, CallToolResult, ProgressNotificationParam, Tool, ToolAnnotations,
+    },
     service::{RequestContext, RoleServer},
 };
 use tracing::{debug, instrument};
@@ -14,16 +17,19 @@ use crate::neovim::NeovimClientTrait;
 
 use super::core::NeovimMcpServer;
 
+/// Interval between progress notifications sent while a tool is running
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Implementation of From<&dyn DynamicTool> for rmcp::model::Tool
 ///
 /// This implementation automatically injects the `connection_id` parameter into the JSON schema
-/// properties and required fields, ensuring that all dynamic tools are compatible with the
-/// MCP protocol's requirement for connection-scoped operations.
+/// properties, ensuring that all dynamic tools are compatible with the MCP protocol's
+/// requirement for connection-scoped operations.
 ///
 /// The injected `connection_id` parameter follows the standard format:
 /// - type: "string"
-/// - description: "Unique identifier for the target Neovim instance"
-/// - required: true (added to the required array if not already present)
+/// - description: "Unique identifier for the target Neovim instance (optional, ...)"
+/// - required: false (the default connection is used when omitted)
 impl From<&dyn DynamicTool> for Tool {
     fn from(val: &dyn DynamicTool) -> Self {
         let mut schema = val
@@ -45,25 +51,14 @@ impl From<&dyn DynamicTool> for Tool {
             .or_insert_with(|| {
                 serde_json::json!({
                     "type": "string",
-                    "description": "Unique identifier for the target Neovim instance"
+                    "description": "Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)"
                 })
             });
 
-        // Add connection_id to required fields if not already present
-        let required = schema
-            .entry("required".to_string())
-            .or_insert_with(|| serde_json::json!([]))
-            .as_array_mut()
-            .expect("required should be an array");
-
-        if !required.iter().any(|v| v.as_str() == Some("connection_id")) {
-            required.push(serde_json::json!("connection_id"));
-        }
         let mut tool = Tool::new(val.name().to_owned(), val.description().to_owned(), schema);
-        tool.annotations = Some(ToolAnnotations::with_title(format!(
-            "Dynamic: {}",
-            val.name()
-        )));
+        let mut annotations = val.annotations();
+        annotations.title = Some(format!("Dynamic: {}", val.name()));
+        tool.annotations = Some(annotations);
         tool
     }
 }
@@ -93,6 +88,11 @@ pub trait DynamicTool: Send + Sync {
     fn input_schema(&self) -> &serde_json::Value;
     fn validate_input(&self, arguments: &serde_json::Value) -> Result<(), McpError>;
 
+    /// Behavior hints of the tool, the title is set by the router
+    fn annotations(&self) -> ToolAnnotations {
+        ToolAnnotations::new()
+    }
+
     async fn call(
         &self,
         client: dashmap::mapref::one::Ref<'_, String, Box<dyn NeovimClientTrait + Send>>,
@@ -101,6 +101,7 @@ pub trait DynamicTool: Send + Sync {
 }
 
 /// Hybrid router that combines static tools (from #[tool_router] macro) with dynamic tools
+#[derive(Clone)]
 pub struct HybridToolRouter {
     /// Static tools from #[tool_router] macro
     static_router: ToolRouter<NeovimMcpServer>,
@@ -203,6 +204,21 @@ impl HybridToolRouter {
         self.static_router.has_route(tool_name)
     }
 
+    /// Whether the tool, as routed for `connection_id`, is annotated as read-only
+    pub fn is_read_only(&self, tool_name: &str, connection_id: Option<&str>) -> bool {
+        if let Some(tools_for_name) = self.dynamic_tools.get(tool_name) {
+            return connection_id
+                .and_then(|connection_id| tools_for_name.get(connection_id))
+                .and_then(|tool| tool.annotations().read_only_hint)
+                .unwrap_or(false);
+        }
+        self.static_router
+            .get(tool_name)
+            .and_then(|tool| tool.annotations.as_ref())
+            .and_then(|annotations| annotations.read_only_hint)
+            .unwrap_or(false)
+    }
+
     /// List all available tools (static + dynamic) for MCP list_tools request
     #[instrument(skip(self))]
     pub fn list_all_tools(&self) -> Vec<Tool> {
@@ -284,14 +300,60 @@ impl HybridToolRouter {
         tools
     }
 
-    /// Main tool call dispatch method for ServerHandler integration
-    #[instrument(skip(self, server, arguments, _context))]
+    /// Main tool call dispatch method for ServerHandler integration.
+    ///
+    /// When the request carries a progress token, progress notifications are sent
+    /// until the tool finishes. A cancelled request stops waiting on the tool, which
+    /// drops its pending Neovim and LSP requests.
+    #[instrument(skip(self, server, arguments, context))]
     pub async fn call_tool(
         &self,
         server: &NeovimMcpServer,
         tool_name: &str,
         arguments: serde_json::Value,
-        _context: RequestContext<RoleServer>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let progress_token = context.meta.get_progress_token();
+        let peer = context.peer.clone();
+        let ct = context.ct.clone();
+
+        let call = self.dispatch(server, tool_name, arguments, context);
+        tokio::pin!(call);
+        let start = tokio::time::Instant::now();
+        let mut ticks = tokio::time::interval_at(start + PROGRESS_INTERVAL, PROGRESS_INTERVAL);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                result = &mut call => return result,
+                _ = ct.cancelled() => {
+                    debug!("Tool '{}' cancelled by the client", tool_name);
+                    return Err(McpError::internal_error(
+                        format!("Tool '{}' was cancelled", tool_name),
+                        None,
+                    ));
+                }
+                _ = ticks.tick(), if progress_token.is_some() => {
+                    let Some(progress_token) = progress_token.clone() else {
+                        continue;
+                    };
+                    let elapsed = start.elapsed().as_secs();
+                    let progress = ProgressNotificationParam::new(progress_token, elapsed as f64)
+                        .with_message(format!("{} running for {}s", tool_name, elapsed));
+                    if let Err(e) = peer.notify_progress(progress).await {
+                        debug!("Failed to notify progress of '{}': {}", tool_name, e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Route a tool call to the dynamic tool of its connection or the static router
+    async fn dispatch(
+        &self,
+        server: &NeovimMcpServer,
+        tool_name: &str,
+        arguments: serde_json::Value,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
         debug!("HybridToolRouter dispatching tool: {}", tool_name);
 
@@ -300,22 +362,12 @@ impl HybridToolRouter {
             debug!("Found dynamic tool variants for: {}", tool_name);
 
             // Extract connection_id from arguments to route to the correct tool instance
-            let connection_id = arguments
-                .get("connection_id")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| {
-                    McpError::invalid_params(
-                        format!(
-                            "Dynamic tool '{}' requires connection_id parameter",
-                            tool_name
-                        ),
-                        None,
-                    )
-                })?;
+            let connection_id = server
+                .resolve_connection_id(arguments.get("connection_id").and_then(|v| v.as_str()))?;
 
-            let client = server.get_connection(connection_id)?;
+            let client = server.get_connection(Some(&connection_id))?;
 
-            if let Some(dynamic_tool) = tools_for_name.get(connection_id) {
+            if let Some(dynamic_tool) = tools_for_name.get(&connection_id) {
                 debug!(
                     "Executing dynamic tool: {} for connection: {}",
                     tool_name, connection_id
@@ -346,7 +398,7 @@ impl HybridToolRouter {
                 .unwrap_or(&serde_json::Map::new())
                 .clone(),
         );
-        let tool_context = ToolCallContext::new(server, request_param, _context);
+        let tool_context = ToolCallContext::new(server, request_param, context);
         self.static_router.call(tool_context).await
     }
 
@@ -469,7 +521,7 @@ mod tests {
         assert_eq!(connection_id_prop.get("type").unwrap(), "string");
         assert_eq!(
             connection_id_prop.get("description").unwrap(),
-            "Unique identifier for the target Neovim instance"
+            "Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)"
         );
 
         // Check that original properties are preserved
@@ -478,9 +530,9 @@ mod tests {
         assert_eq!(message_prop.get("type").unwrap(), "string");
         assert_eq!(message_prop.get("description").unwrap(), "A test message");
 
-        // Check that connection_id was added to required fields
+        // Check that connection_id is optional, falling back to the default connection
         let required = schema.get("required").unwrap().as_array().unwrap();
-        assert!(required.contains(&json!("connection_id")));
+        assert!(!required.contains(&json!("connection_id")));
         assert!(required.contains(&json!("message")));
 
         // Check annotations
@@ -567,7 +619,7 @@ mod tests {
         assert!(properties.contains_key("value"));
 
         let required = schema.get("required").unwrap().as_array().unwrap();
-        assert!(required.contains(&json!("connection_id")));
+        assert!(!required.contains(&json!("connection_id")));
         assert!(required.contains(&json!("value")));
     }
 }
@@ -1,7 +1,15 @@
 use rmcp::{
-    model::{CallToolRequestParams, ReadResourceRequestParams},
+    ClientHandler, ErrorData as McpError, RoleClient,
+    model::{
+        ArgumentInfo, CallToolRequest, CallToolRequestParams, ClientCapabilities, ClientInfo,
+        ClientRequest, CompleteRequestParams, CompletionContext, CreateElicitationRequestParams,
+        CreateElicitationResult, ElicitationAction, GetPromptRequestParams, Implementation,
+        LoggingLevel, LoggingMessageNotificationParam, ProgressNotificationParam,
+        ReadResourceRequestParams, Reference, ResourceContents, ResourceUpdatedNotificationParam,
+        SetLevelRequestParams, SubscribeRequestParams, UnsubscribeRequestParams,
+    },
     serde_json::{Map, Value},
-    service::ServiceExt,
+    service::{NotificationContext, PeerRequestOptions, RequestContext, ServiceExt},
     transport::{ConfigureCommandExt, TokioChildProcess},
 };
 use tokio::process::Command;
@@ -214,6 +222,25 @@ async fn test_list_tools() -> Result<(), Box<dyn std::error::Error>> {
         assert!(!tool.description.as_ref().unwrap().is_empty());
     }
 
+    // Verify every tool declares its behavior
+    for tool in &tools.tools {
+        let annotations = tool
+            .annotations
+            .as_ref()
+            .unwrap_or_else(|| panic!("{} has no annotations", tool.name));
+        assert!(annotations.read_only_hint.is_some());
+        assert!(annotations.open_world_hint.is_some());
+    }
+
+    // Verify every tool but the plain text `read` publishes an object output schema
+    for tool in tools.tools.iter().filter(|tool| tool.name != "read") {
+        let schema = tool
+            .output_schema
+            .as_ref()
+            .unwrap_or_else(|| panic!("{} has no output schema", tool.name));
+        assert_eq!(schema.get("type"), Some(&Value::String("object".into())));
+    }
+
     service.cancel().await?;
     info!("List tools test completed successfully");
 
@@ -431,6 +458,10 @@ async fn test_list_buffers_tool() -> Result<(), Box<dyn std::error::Error>> {
         panic!("No content in list buffers result");
     }
 
+    // The same buffers are returned as structured content
+    let structured = result.structured_content.expect("structured content");
+    assert_eq!(structured["result"][0]["id"], 1);
+
     // Cleanup happens automatically via guard
     service.cancel().await?;
     info!("List buffers tool test completed successfully");
@@ -438,6 +469,65 @@ async fn test_list_buffers_tool() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[tokio::test]
+#[traced_test]
+async fn test_default_connection() -> Result<(), Box<dyn std::error::Error>> {
+    let (service, connection_id, _guard) = setup_connected_service!();
+
+    // The only connection is the default one
+    let result = service
+        .call_tool(call_tool_req("list_buffers", Some(Map::new())))
+        .await?;
+    let text = result.content.first().and_then(|c| c.as_text()).unwrap();
+    assert!(text.text.contains("\"id\":1"));
+
+    let mut args = Map::new();
+    args.insert(
+        "connection_id".to_string(),
+        Value::String(connection_id.clone()),
+    );
+    let result = service
+        .call_tool(call_tool_req("set_default_connection", Some(args)))
+        .await?;
+    let text = result.content.first().and_then(|c| c.as_text()).unwrap();
+    assert!(text.text.contains(&connection_id));
+
+    // Unknown connections cannot become the default
+    let mut args = Map::new();
+    args.insert(
+        "connection_id".to_string(),
+        Value::String("unknown".to_string()),
+    );
+    let result = service
+        .call_tool(call_tool_req("set_default_connection", Some(args)))
+        .await;
+    assert!(result.is_err());
+
+    service.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_list_buffers_all_connections() -> Result<(), Box<dyn std::error::Error>> {
+    let (service, connection_id, _guard) = setup_connected_service!();
+
+    let mut args = Map::new();
+    args.insert("all_connections".to_string(), Value::Bool(true));
+    let result = service
+        .call_tool(call_tool_req("list_buffers", Some(args)))
+        .await?;
+    let text = result.content.first().and_then(|c| c.as_text()).unwrap();
+    let by_connection: Value = serde_json::from_str(&text.text)?;
+    let buffers = by_connection[&connection_id]
+        .as_array()
+        .expect("Expected buffers keyed by connection ID");
+    assert!(buffers.iter().any(|buffer| buffer["id"] == 1));
+
+    service.cancel().await?;
+    Ok(())
+}
+
 #[tokio::test]
 #[traced_test]
 async fn test_read_buffer() -> Result<(), Box<dyn std::error::Error>> {
@@ -901,6 +991,34 @@ async fn test_read_workspace_diagnostics() -> Result<(), Box<dyn std::error::Err
     Ok(())
 }
 
+#[traced_test]
+#[tokio::test]
+async fn test_connections_resource_metadata() -> Result<(), Box<dyn std::error::Error>> {
+    let (service, connection_id, _guard) = setup_connected_service!();
+
+    let result = service
+        .read_resource(read_resource_req("nvim-connections://"))
+        .await?;
+    let Some(ResourceContents::TextResourceContents { text, .. }) = result.contents.first() else {
+        panic!("Expected text content in connections resource");
+    };
+
+    let connections: Value = serde_json::from_str(text)?;
+    let connection = connections
+        .as_array()
+        .and_then(|connections| connections.first())
+        .expect("Expected one connection");
+    info!("Connection metadata: {connection:#?}");
+    assert_eq!(connection["id"], Value::String(connection_id));
+    assert_eq!(connection["stale"], Value::Bool(false));
+    assert!(connection["pid"].as_u64().is_some());
+    assert!(connection["nvim_version"].as_str().is_some());
+    assert!(connection["cwd"].as_str().is_some());
+
+    service.cancel().await?;
+    Ok(())
+}
+
 #[traced_test]
 #[tokio::test]
 async fn test_lsp_organize_imports_non_existent_file() -> Result<(), Box<dyn std::error::Error>> {
@@ -1092,15 +1210,16 @@ async fn test_lua_tools_end_to_end_workflow() -> Result<(), Box<dyn std::error::
     let tools_result = service.list_tools(Default::default()).await?;
     info!("Available tools after Lua setup: {:?}", tools_result);
 
-    // Check if our custom tool is discovered
-    let tools_contain_save_buffer = tools_result
+    // Check if our custom tool is discovered, with the hints declared in setup()
+    let save_buffer = tools_result
         .tools
         .iter()
-        .any(|tool| tool.name == "save_buffer");
-    assert!(
-        tools_contain_save_buffer,
-        "Custom save_buffer tool should be discovered"
-    );
+        .find(|tool| tool.name == "save_buffer")
+        .expect("Custom save_buffer tool should be discovered");
+    let annotations = save_buffer.annotations.as_ref().unwrap();
+    assert_eq!(annotations.read_only_hint, Some(false));
+    assert_eq!(annotations.destructive_hint, Some(false));
+    assert_eq!(annotations.idempotent_hint, Some(true));
 
     // Test custom tool execution
     let mut tool_args = Map::new();
@@ -1394,10 +1513,7 @@ async fn test_lsp_call_hierarchy_prepare() -> Result<(), Box<dyn std::error::Err
             "buffer_id": 0
         }),
     );
-    args.insert(
-        "lsp_client_name".to_string(),
-        Value::String("gopls".to_string()),
-    );
+    // lsp_client_name is omitted, the output names the client that was chosen
     args.insert(
         "line".to_string(),
         Value::Number(serde_json::Number::from(16)),
@@ -1419,6 +1535,8 @@ async fn test_lsp_call_hierarchy_prepare() -> Result<(), Box<dyn std::error::Err
         Ok(tool_result) => {
             info!("Tool executed successfully: {:?}", tool_result);
             assert!(!tool_result.content.is_empty());
+            let structured = tool_result.structured_content.expect("structured content");
+            assert_eq!(structured["lsp_client_name"], "gopls");
         }
         Err(e) => {
             panic!("Tool failed as expected (LSP may not be ready): {}", e);
@@ -1431,6 +1549,195 @@ async fn test_lsp_call_hierarchy_prepare() -> Result<(), Box<dyn std::error::Err
     Ok(())
 }
 
+#[tokio::test]
+#[traced_test]
+async fn test_lsp_references_without_client_name() -> Result<(), Box<dyn std::error::Error>> {
+    info!("Testing lsp_references with automatic client selection");
+    let (service, connection_id, _guard) = setup_connected_service!(
+        get_testdata_path("cfg_lsp.lua").to_str().unwrap(),
+        get_testdata_path("call_hierarchy.go").to_str().unwrap()
+    );
+
+    wait_for_lsp_ready!(service, connection_id.clone(), "gopls".to_string());
+
+    // lsp_client_name is omitted, the server selects gopls from the attached clients
+    let mut args = Map::new();
+    args.insert(
+        "connection_id".to_string(),
+        Value::String(connection_id.clone()),
+    );
+    args.insert(
+        "document".to_string(),
+        serde_json::json!({
+            "buffer_id": 0
+        }),
+    );
+    args.insert(
+        "line".to_string(),
+        Value::Number(serde_json::Number::from(16)),
+    );
+    args.insert(
+        "character".to_string(),
+        Value::Number(serde_json::Number::from(5)),
+    );
+    args.insert("include_declaration".to_string(), Value::Bool(true));
+
+    let result = service
+        .call_tool(call_tool_req("lsp_references", Some(args)))
+        .await?;
+    info!("References result: {:?}", result);
+    assert!(!result.content.is_empty());
+    assert!(
+        serde_json::to_string(&result)
+            .unwrap()
+            .contains("call_hierarchy.go")
+    );
+
+    service.cancel().await?;
+    info!("Automatic client selection test completed successfully");
+    Ok(())
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_lsp_hover_with_symbol_locator() -> Result<(), Box<dyn std::error::Error>> {
+    info!("Testing lsp_hover with symbol locators");
+    let (service, connection_id, _guard) = setup_connected_service!(
+        get_testdata_path("cfg_lsp.lua").to_str().unwrap(),
+        get_testdata_path("call_hierarchy.go").to_str().unwrap()
+    );
+
+    wait_for_lsp_ready!(service, connection_id.clone(), "gopls".to_string());
+
+    let hover_args = |symbol: Value| {
+        let mut args = Map::new();
+        args.insert(
+            "connection_id".to_string(),
+            Value::String(connection_id.clone()),
+        );
+        args.insert("document".to_string(), serde_json::json!({"buffer_id": 0}));
+        args.insert("symbol".to_string(), symbol);
+        args
+    };
+
+    // Symbol path resolved through document symbols
+    let result = service
+        .call_tool(call_tool_req(
+            "lsp_hover",
+            Some(hover_args(Value::String("caller".to_string()))),
+        ))
+        .await?;
+    assert!(serde_json::to_string(&result).unwrap().contains("caller"));
+
+    // Text match on a zero-based line
+    let result = service
+        .call_tool(call_tool_req(
+            "lsp_hover",
+            Some(hover_args(
+                serde_json::json!({"line": 16, "text_match": "caller"}),
+            )),
+        ))
+        .await?;
+    assert!(serde_json::to_string(&result).unwrap().contains("caller"));
+
+    // Unresolvable locators are rejected with a clear error
+    let result = service
+        .call_tool(call_tool_req(
+            "lsp_hover",
+            Some(hover_args(Value::String("missing_function".to_string()))),
+        ))
+        .await;
+    assert!(result.is_err());
+    assert!(
+        result
+            .unwrap_err()
+            .to_string()
+            .contains("Symbol `missing_function` not found")
+    );
+
+    service.cancel().await?;
+    info!("Symbol locator test completed successfully");
+    Ok(())
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_reconnect_after_neovim_restart() -> Result<(), Box<dyn std::error::Error>> {
+    let ipc_path = generate_random_ipc_path();
+    let guard = setup_test_neovim_instance(&ipc_path).await?;
+    let service = create_mcp_service!(&ipc_path);
+    let connection_id = b3sum(&ipc_path)[..7].to_string();
+
+    let list_buffers = |connection_id: &str| {
+        let mut args = Map::new();
+        args.insert(
+            "connection_id".to_string(),
+            Value::String(connection_id.to_string()),
+        );
+        call_tool_req("list_buffers", Some(args))
+    };
+    service.call_tool(list_buffers(&connection_id)).await?;
+
+    // Killing Neovim leaves a stale connection that cannot be reconnected yet
+    drop(guard);
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    let result = service.call_tool(list_buffers(&connection_id)).await;
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("stale"));
+
+    // Once Neovim listens on the same address again, the same ID reconnects
+    let _guard = setup_test_neovim_instance(&ipc_path).await?;
+    let result = service.call_tool(list_buffers(&connection_id)).await?;
+    assert!(!result.content.is_empty());
+
+    service.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_navigate_counts_code_points() -> Result<(), Box<dyn std::error::Error>> {
+    let (service, connection_id, _guard) = setup_connected_service!();
+
+    let temp_dir = tempfile::tempdir()?;
+    let test_file = temp_dir.path().join("test_unicode.txt");
+    // Each CJK character is 3 bytes and the emoji is 2 UTF-16 code units
+    std::fs::write(&test_file, "中文😀 target\n")?;
+
+    let mut navigate_args = Map::new();
+    navigate_args.insert(
+        "connection_id".to_string(),
+        Value::String(connection_id.clone()),
+    );
+    navigate_args.insert(
+        "document".to_string(),
+        serde_json::json!({"absolute_path": test_file.to_string_lossy()}),
+    );
+    navigate_args.insert(
+        "symbol".to_string(),
+        serde_json::json!({"line": 0, "text_match": "target"}),
+    );
+    service
+        .call_tool(call_tool_req("navigate", Some(navigate_args)))
+        .await?;
+
+    let mut cursor_args = Map::new();
+    cursor_args.insert(
+        "connection_id".to_string(),
+        Value::String(connection_id.clone()),
+    );
+    let result = service
+        .call_tool(call_tool_req("cursor_position", Some(cursor_args)))
+        .await?;
+    let text = result.content[0].as_text().unwrap();
+    let cursor_data: serde_json::Value = serde_json::from_str(&text.text)?;
+    assert_eq!(cursor_data["row"], 0);
+    assert_eq!(cursor_data["col"], 4);
+
+    service.cancel().await?;
+    Ok(())
+}
+
 #[tokio::test]
 #[traced_test]
 async fn test_lsp_call_hierarchy_incoming_calls() -> Result<(), Box<dyn std::error::Error>> {
@@ -2001,3 +2308,706 @@ async fn test_read_invalid_paths() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[tokio::test]
+#[traced_test]
+async fn test_spawn_nvim_tool() -> Result<(), Box<dyn std::error::Error>> {
+    let service = create_mcp_service!();
+    let temp_dir = tempfile::tempdir()?;
+
+    let mut args = Map::new();
+    args.insert(
+        "cwd".to_string(),
+        Value::String(temp_dir.path().to_string_lossy().to_string()),
+    );
+    args.insert("init_file".to_string(), Value::String("NONE".to_string()));
+    let result = service
+        .call_tool(call_tool_req("spawn_nvim", Some(args)))
+        .await?;
+    let text = result.content.first().and_then(|c| c.as_text()).unwrap();
+    let spawned: Value = serde_json::from_str(&text.text)?;
+    let connection_id = spawned["connection_id"].as_str().unwrap().to_string();
+    let pid = spawned["pid"].as_u64().unwrap() as u32;
+    assert!(crate::server::core::is_pid_alive(pid));
+
+    // The spawned instance runs in the requested working directory
+    let mut args = Map::new();
+    args.insert(
+        "connection_id".to_string(),
+        Value::String(connection_id.clone()),
+    );
+    args.insert(
+        "code".to_string(),
+        Value::String("return vim.fn.getcwd()".to_string()),
+    );
+    let result = service
+        .call_tool(call_tool_req("exec_lua", Some(args)))
+        .await?;
+    let text = result.content.first().and_then(|c| c.as_text()).unwrap();
+    let canonical_cwd = std::fs::canonicalize(temp_dir.path())?;
+    assert!(text.text.contains(canonical_cwd.to_str().unwrap()));
+
+    // Disconnecting stops the instance
+    let mut args = Map::new();
+    args.insert("connection_id".to_string(), Value::String(connection_id));
+    service
+        .call_tool(call_tool_req("disconnect", Some(args)))
+        .await?;
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    assert!(!crate::server::core::is_pid_alive(pid));
+
+    service.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_spawn_nvim_embed() -> Result<(), Box<dyn std::error::Error>> {
+    let service = create_mcp_service!();
+    let temp_dir = tempfile::tempdir()?;
+
+    let mut args = Map::new();
+    args.insert(
+        "cwd".to_string(),
+        Value::String(temp_dir.path().to_string_lossy().to_string()),
+    );
+    args.insert("init_file".to_string(), Value::String("NONE".to_string()));
+    args.insert("embed".to_string(), Value::Bool(true));
+    let result = service
+        .call_tool(call_tool_req("spawn_nvim", Some(args)))
+        .await?;
+    let text = result.content.first().and_then(|c| c.as_text()).unwrap();
+    let spawned: Value = serde_json::from_str(&text.text)?;
+    let connection_id = spawned["connection_id"].as_str().unwrap().to_string();
+    let pid = spawned["pid"].as_u64().unwrap() as u32;
+    assert_eq!(spawned["target"], format!("embed:{pid}"));
+    assert!(crate::server::core::is_pid_alive(pid));
+
+    // The embedded instance answers over its stdio
+    let mut args = Map::new();
+    args.insert(
+        "connection_id".to_string(),
+        Value::String(connection_id.clone()),
+    );
+    args.insert(
+        "code".to_string(),
+        Value::String("return vim.fn.getpid()".to_string()),
+    );
+    let result = service
+        .call_tool(call_tool_req("exec_lua", Some(args)))
+        .await?;
+    let text = result.content.first().and_then(|c| c.as_text()).unwrap();
+    assert!(text.text.contains(&pid.to_string()));
+
+    // Disconnecting stops the instance
+    let mut args = Map::new();
+    args.insert("connection_id".to_string(), Value::String(connection_id));
+    service
+        .call_tool(call_tool_req("disconnect", Some(args)))
+        .await?;
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    assert!(!crate::server::core::is_pid_alive(pid));
+
+    service.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_editor_prompts() -> Result<(), Box<dyn std::error::Error>> {
+    let (service, connection_id, _guard) = setup_connected_service!();
+
+    let prompts = service.list_prompts(Default::default()).await?;
+    let names: Vec<&str> = prompts.prompts.iter().map(|p| p.name.as_str()).collect();
+    assert!(names.contains(&"fix_diagnostics"));
+    assert!(names.contains(&"explain_symbol"));
+    assert!(names.contains(&"review_selection"));
+
+    // Put some text in the buffer and select its second line
+    let mut args = Map::new();
+    args.insert(
+        "connection_id".to_string(),
+        Value::String(connection_id.clone()),
+    );
+    args.insert(
+        "code".to_string(),
+        Value::String(
+            "vim.api.nvim_buf_set_lines(0, 0, -1, false, { 'local answer = 42', 'return answer' })
+             vim.api.nvim_win_set_cursor(0, { 2, 0 })
+             vim.cmd('normal! Vy')"
+                .to_string(),
+        ),
+    );
+    service
+        .call_tool(call_tool_req("exec_lua", Some(args)))
+        .await?;
+
+    let mut arguments = Map::new();
+    arguments.insert(
+        "connection_id".to_string(),
+        Value::String(connection_id.clone()),
+    );
+    let result = service
+        .get_prompt(
+            GetPromptRequestParams::new("review_selection").with_arguments(arguments.clone()),
+        )
+        .await?;
+    let text = format!("{:?}", result.messages);
+    assert!(text.contains("return answer"), "{text}");
+    assert!(!text.contains("local answer"), "{text}");
+
+    let result = service
+        .get_prompt(GetPromptRequestParams::new("explain_symbol").with_arguments(arguments))
+        .await?;
+    let text = format!("{:?}", result.messages);
+    assert!(text.contains("`return`"), "{text}");
+
+    // The default connection is used when connection_id is omitted
+    let result = service
+        .get_prompt(GetPromptRequestParams::new("fix_diagnostics"))
+        .await?;
+    assert_eq!(result.messages.len(), 1);
+
+    service.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_lua_prompts_and_resources() -> Result<(), Box<dyn std::error::Error>> {
+    let service = create_mcp_service!();
+
+    let ipc_path = generate_random_socket_path();
+    let child = crate::test_utils::setup_neovim_instance_ipc_advance(
+        &ipc_path,
+        "src/testdata/cfg_lsp.lua",
+        "src/testdata/main.go",
+    )
+    .await;
+    let _guard = NeovimIpcGuard::new(child, ipc_path.clone());
+
+    let mut connect_args = Map::new();
+    connect_args.insert("target".to_string(), Value::String(ipc_path));
+    let result = service
+        .call_tool(call_tool_req("connect", Some(connect_args)))
+        .await?;
+    let connection_id = extract_connection_id(&result)?;
+
+    // Prompts registered through custom_prompts are listed next to the built-in ones
+    let prompts = service.list_prompts(Default::default()).await?;
+    let prompt = prompts
+        .prompts
+        .iter()
+        .find(|p| p.name == "summarize_buffer")
+        .expect("Lua prompt should be listed");
+    let arguments = prompt.arguments.as_ref().expect("Lua prompt has arguments");
+    assert!(arguments.iter().any(|a| a.name == "focus"));
+    assert!(arguments.iter().any(|a| a.name == "connection_id"));
+
+    let mut arguments = Map::new();
+    arguments.insert("focus".to_string(), Value::String("imports".to_string()));
+    let result = service
+        .get_prompt(GetPromptRequestParams::new("summarize_buffer").with_arguments(arguments))
+        .await?;
+    let text = format!("{:?}", result.messages);
+    assert!(text.contains("focusing on imports"), "{text}");
+
+    // Required arguments are checked before calling the handler
+    let result = service
+        .get_prompt(GetPromptRequestParams::new("summarize_buffer"))
+        .await;
+    assert!(result.is_err(), "Missing required argument should fail");
+
+    // Resources registered through custom_resources can be listed and read
+    let resources = service.list_resources(Default::default()).await?;
+    assert!(
+        resources
+            .resources
+            .iter()
+            .any(|r| r.raw.uri == "test://buffer-count")
+    );
+
+    let result = service
+        .read_resource(read_resource_req("test://buffer-count"))
+        .await?;
+    let Some(ResourceContents::TextResourceContents { text, .. }) = result.contents.first() else {
+        panic!("Expected text resource contents");
+    };
+    let value: Value = serde_json::from_str(text)?;
+    assert!(value["count"].as_u64().unwrap() >= 1);
+
+    // Disconnecting removes them again
+    let mut disconnect_args = Map::new();
+    disconnect_args.insert("connection_id".to_string(), Value::String(connection_id));
+    service
+        .call_tool(call_tool_req("disconnect", Some(disconnect_args)))
+        .await?;
+    let prompts = service.list_prompts(Default::default()).await?;
+    assert!(!prompts.prompts.iter().any(|p| p.name == "summarize_buffer"));
+    let result = service
+        .read_resource(read_resource_req("test://buffer-count"))
+        .await;
+    assert!(result.is_err());
+
+    service.cancel().await?;
+    Ok(())
+}
+
+/// Client that forwards the URIs of resource update notifications
+#[derive(Clone)]
+struct ResourceUpdateRecorder(tokio::sync::mpsc::UnboundedSender<String>);
+
+impl ClientHandler for ResourceUpdateRecorder {
+    async fn on_resource_updated(
+        &self,
+        params: ResourceUpdatedNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        let _ = self.0.send(params.uri);
+    }
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_resource_subscriptions() -> Result<(), Box<dyn std::error::Error>> {
+    let ipc_path = generate_random_ipc_path();
+    let _guard = setup_test_neovim_instance(&ipc_path).await?;
+    let connection_id = b3sum(&ipc_path)[..7].to_string();
+
+    let (tx, mut updates) = tokio::sync::mpsc::unbounded_channel();
+    let command = Command::new(get_compiled_binary()).configure(|cmd| {
+        cmd.args(["--connect", &ipc_path]);
+    });
+    let service = ResourceUpdateRecorder(tx)
+        .serve(TokioChildProcess::new(command)?)
+        .await?;
+
+    let buffer_uri = format!("nvim-diagnostics://{connection_id}/buffer/1");
+    service
+        .subscribe(SubscribeRequestParams::new(&buffer_uri))
+        .await?;
+
+    // Resources without change tracking cannot be subscribed to
+    let result = service
+        .subscribe(SubscribeRequestParams::new("nvim-connections://"))
+        .await;
+    assert!(result.is_err());
+
+    // Several changes in quick succession are reported once
+    let set_diagnostics = |message: &str| {
+        let mut args = Map::new();
+        args.insert(
+            "connection_id".to_string(),
+            Value::String(connection_id.clone()),
+        );
+        args.insert(
+            "code".to_string(),
+            Value::String(format!(
+                "local ns = vim.api.nvim_create_namespace('nvim-mcp-test')
+                 vim.diagnostic.set(ns, 1, {{ {{ lnum = 0, col = 0, message = '{message}' }} }})"
+            )),
+        );
+        call_tool_req("exec_lua", Some(args))
+    };
+    service.call_tool(set_diagnostics("first")).await?;
+    service.call_tool(set_diagnostics("second")).await?;
+
+    let uri = tokio::time::timeout(std::time::Duration::from_secs(5), updates.recv())
+        .await?
+        .expect("update notification");
+    assert_eq!(uri, buffer_uri);
+    tokio::time::sleep(std::time::Duration::from_millis(800)).await;
+    assert!(updates.try_recv().is_err(), "changes should be debounced");
+
+    // No more updates once unsubscribed
+    service
+        .unsubscribe(UnsubscribeRequestParams::new(&buffer_uri))
+        .await?;
+    service.call_tool(set_diagnostics("third")).await?;
+    tokio::time::sleep(std::time::Duration::from_millis(800)).await;
+    assert!(updates.try_recv().is_err());
+
+    service.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_buffer_and_file_resources() -> Result<(), Box<dyn std::error::Error>> {
+    let (service, connection_id, _guard) = setup_connected_service!(
+        get_testdata_path("cfg_lsp.lua").to_str().unwrap(),
+        get_testdata_path("main.go").to_str().unwrap()
+    );
+
+    // Open buffers are listed with the MIME type of their filetype
+    let resources = service.list_resources(Default::default()).await?;
+    let buffer = resources
+        .resources
+        .iter()
+        .find(|r| {
+            r.raw
+                .uri
+                .starts_with(&format!("nvim-buffer://{connection_id}/"))
+        })
+        .expect("open buffer should be listed");
+    assert_eq!(buffer.raw.mime_type.as_deref(), Some("text/x-go"));
+    assert!(buffer.raw.size.unwrap() > 0);
+
+    let result = service
+        .read_resource(read_resource_req(buffer.raw.uri.clone()))
+        .await?;
+    let Some(ResourceContents::TextResourceContents {
+        text, mime_type, ..
+    }) = result.contents.first()
+    else {
+        panic!("Expected text resource contents");
+    };
+    assert!(text.contains("package main"));
+    assert_eq!(mime_type.as_deref(), Some("text/x-go"));
+
+    // Files are read by their path relative to the project root
+    let result = service
+        .read_resource(read_resource_req(format!(
+            "nvim-file://{connection_id}/Cargo.toml"
+        )))
+        .await?;
+    let Some(ResourceContents::TextResourceContents {
+        text, mime_type, ..
+    }) = result.contents.first()
+    else {
+        panic!("Expected text resource contents");
+    };
+    assert!(text.contains("[package]"));
+    assert_eq!(mime_type.as_deref(), Some("application/toml"));
+
+    let result = service
+        .read_resource(read_resource_req(format!(
+            "nvim-file://{connection_id}/../etc/passwd"
+        )))
+        .await;
+    assert!(result.is_err(), "Paths outside the project are rejected");
+
+    let templates = service.list_resource_templates(Default::default()).await?;
+    assert!(
+        templates
+            .resource_templates
+            .iter()
+            .any(|t| t.raw.uri_template == "nvim-file://{connection_id}/{path}")
+    );
+
+    service.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_argument_completion() -> Result<(), Box<dyn std::error::Error>> {
+    let (service, connection_id, _guard) = setup_connected_service!();
+
+    let argument = |name: &str, value: &str| ArgumentInfo {
+        name: name.to_string(),
+        value: value.to_string(),
+    };
+
+    // Connection IDs of prompt arguments
+    let result = service
+        .complete(CompleteRequestParams::new(
+            Reference::for_prompt("fix_diagnostics"),
+            argument("connection_id", &connection_id[..3]),
+        ))
+        .await?;
+    assert_eq!(result.completion.values, vec![connection_id.clone()]);
+
+    // Buffer IDs of resource template variables, from the chosen connection
+    let context = CompletionContext::with_arguments(
+        [("connection_id".to_string(), connection_id.clone())].into(),
+    );
+    let result = service
+        .complete(
+            CompleteRequestParams::new(
+                Reference::for_resource("nvim-buffer://{connection_id}/{buffer_id}"),
+                argument("buffer_id", ""),
+            )
+            .with_context(context.clone()),
+        )
+        .await?;
+    assert!(result.completion.values.contains(&"1".to_string()));
+
+    // Project-relative paths, one directory level at a time
+    let result = service
+        .complete(
+            CompleteRequestParams::new(
+                Reference::for_resource("nvim-file://{connection_id}/{path}"),
+                argument("path", "src/test"),
+            )
+            .with_context(context.clone()),
+        )
+        .await?;
+    assert!(
+        result
+            .completion
+            .values
+            .contains(&"src/testdata/".to_string()),
+        "{:?}",
+        result.completion.values
+    );
+
+    let result = service
+        .complete(
+            CompleteRequestParams::new(
+                Reference::for_resource("nvim-file://{connection_id}/{path}"),
+                argument("path", "../"),
+            )
+            .with_context(context),
+        )
+        .await?;
+    assert!(result.completion.values.is_empty());
+
+    // Only the arguments of the referenced prompt are completed
+    let result = service
+        .complete(CompleteRequestParams::new(
+            Reference::for_prompt("fix_diagnostics"),
+            argument("buffer_id", ""),
+        ))
+        .await?;
+    assert!(result.completion.values.is_empty());
+
+    service.cancel().await?;
+    Ok(())
+}
+
+/// Client that forwards the log messages it receives
+#[derive(Clone)]
+struct LogRecorder(tokio::sync::mpsc::UnboundedSender<LoggingMessageNotificationParam>);
+
+impl ClientHandler for LogRecorder {
+    async fn on_logging_message(
+        &self,
+        params: LoggingMessageNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        let _ = self.0.send(params);
+    }
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_neovim_messages_are_logged() -> Result<(), Box<dyn std::error::Error>> {
+    let ipc_path = generate_random_ipc_path();
+    let _guard = setup_test_neovim_instance(&ipc_path).await?;
+    let connection_id = b3sum(&ipc_path)[..7].to_string();
+
+    let (tx, mut messages) = tokio::sync::mpsc::unbounded_channel();
+    let command = Command::new(get_compiled_binary()).configure(|cmd| {
+        cmd.args(["--connect", &ipc_path]);
+    });
+    let service = LogRecorder(tx)
+        .serve(TokioChildProcess::new(command)?)
+        .await?;
+
+    let notify = |text: &str, level: &str| {
+        let mut args = Map::new();
+        args.insert(
+            "connection_id".to_string(),
+            Value::String(connection_id.clone()),
+        );
+        args.insert(
+            "code".to_string(),
+            Value::String(format!("vim.notify('{text}', vim.log.levels.{level})")),
+        );
+        call_tool_req("exec_lua", Some(args))
+    };
+
+    // Messages below the requested level are not sent
+    service
+        .set_level(SetLevelRequestParams::new(LoggingLevel::Error))
+        .await?;
+    service.call_tool(notify("quiet warning", "WARN")).await?;
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    service
+        .set_level(SetLevelRequestParams::new(LoggingLevel::Warning))
+        .await?;
+    service.call_tool(notify("loud warning", "WARN")).await?;
+
+    let message = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        loop {
+            let message = messages.recv().await.expect("log message");
+            if message.logger.as_deref() == Some("nvim_mcp::neovim::message") {
+                return message;
+            }
+        }
+    })
+    .await?;
+    assert_eq!(message.level, LoggingLevel::Warning);
+    assert_eq!(message.data["message"], "loud warning");
+    assert_eq!(message.data["source"], "notify");
+
+    service.cancel().await?;
+    Ok(())
+}
+
+/// Client that forwards the progress notifications it receives
+#[derive(Clone)]
+struct ProgressRecorder(tokio::sync::mpsc::UnboundedSender<ProgressNotificationParam>);
+
+impl ClientHandler for ProgressRecorder {
+    async fn on_progress(
+        &self,
+        params: ProgressNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        let _ = self.0.send(params);
+    }
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_tool_progress_and_cancellation() -> Result<(), Box<dyn std::error::Error>> {
+    let ipc_path = generate_random_ipc_path();
+    let _guard = setup_test_neovim_instance(&ipc_path).await?;
+    let connection_id = b3sum(&ipc_path)[..7].to_string();
+
+    let (tx, mut progress) = tokio::sync::mpsc::unbounded_channel();
+    let command = Command::new(get_compiled_binary()).configure(|cmd| {
+        cmd.args(["--connect", &ipc_path]);
+    });
+    let service = ProgressRecorder(tx)
+        .serve(TokioChildProcess::new(command)?)
+        .await?;
+
+    // Wait for an LSP client that never attaches
+    let mut args = Map::new();
+    args.insert(
+        "connection_id".to_string(),
+        Value::String(connection_id.clone()),
+    );
+    args.insert(
+        "client_name".to_string(),
+        Value::String("missing".to_string()),
+    );
+    args.insert("timeout_ms".to_string(), Value::from(60_000));
+    let request = ClientRequest::CallToolRequest(CallToolRequest::new(call_tool_req(
+        "wait_for_lsp_ready",
+        Some(args),
+    )));
+    let handle = service
+        .peer()
+        .send_request_with_option(request, PeerRequestOptions::no_options())
+        .await?;
+    let progress_token = handle.progress_token.clone();
+
+    let update = tokio::time::timeout(std::time::Duration::from_secs(5), progress.recv())
+        .await?
+        .expect("progress notification");
+    assert_eq!(update.progress_token, progress_token);
+    assert!(update.progress >= 1.0);
+    assert!(
+        update
+            .message
+            .as_deref()
+            .is_some_and(|message| message.starts_with("wait_for_lsp_ready"))
+    );
+
+    // Once cancelled, no more progress is sent and the connection stays usable
+    handle.cancel(Some("test".to_string())).await?;
+    let mut args = Map::new();
+    args.insert("connection_id".to_string(), Value::String(connection_id));
+    let result = tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        service.call_tool(call_tool_req("list_buffers", Some(args))),
+    )
+    .await??;
+    assert!(!result.is_error.unwrap_or(false));
+    while progress.try_recv().is_ok() {}
+    tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+    assert!(progress.try_recv().is_err());
+
+    service.cancel().await?;
+    Ok(())
+}
+
+/// Client that answers confirmations with the next of its answers, and
+/// forwards the messages it was asked to confirm
+#[derive(Clone)]
+struct ConfirmationResponder {
+    answers: std::sync::Arc<std::sync::Mutex<Vec<bool>>>,
+    messages: tokio::sync::mpsc::UnboundedSender<String>,
+}
+
+impl ClientHandler for ConfirmationResponder {
+    async fn create_elicitation(
+        &self,
+        request: CreateElicitationRequestParams,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateElicitationResult, McpError> {
+        if let CreateElicitationRequestParams::FormElicitationParams { message, .. } = request {
+            let _ = self.messages.send(message);
+        }
+        let approve = self.answers.lock().unwrap().remove(0);
+        Ok(CreateElicitationResult::new(ElicitationAction::Accept)
+            .with_content(serde_json::json!({ "approve": approve })))
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo::new(
+            ClientCapabilities::builder().enable_elicitation().build(),
+            Implementation::from_build_env(),
+        )
+    }
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_destructive_tool_confirmation() -> Result<(), Box<dyn std::error::Error>> {
+    let ipc_path = generate_random_ipc_path();
+    let _guard = setup_test_neovim_instance(&ipc_path).await?;
+    let connection_id = b3sum(&ipc_path)[..7].to_string();
+
+    let (tx, mut messages) = tokio::sync::mpsc::unbounded_channel();
+    let command = Command::new(get_compiled_binary()).configure(|cmd| {
+        cmd.args(["--connect", &ipc_path, "--confirm", "exec_lua"]);
+    });
+    let service = ConfirmationResponder {
+        answers: std::sync::Arc::new(std::sync::Mutex::new(vec![false, true])),
+        messages: tx,
+    }
+    .serve(TokioChildProcess::new(command)?)
+    .await?;
+
+    let exec_lua = |code: &str| {
+        let mut args = Map::new();
+        args.insert(
+            "connection_id".to_string(),
+            Value::String(connection_id.clone()),
+        );
+        args.insert("code".to_string(), Value::String(code.to_string()));
+        call_tool_req("exec_lua", Some(args))
+    };
+
+    // A rejected call does not run
+    let rejected = service
+        .call_tool(exec_lua("vim.g.nvim_mcp_rejected = true"))
+        .await;
+    assert!(rejected.is_err());
+    let message = messages.recv().await.expect("confirmation message");
+    assert!(message.starts_with("Allow exec_lua on"));
+    assert!(message.ends_with("vim.g.nvim_mcp_rejected = true"));
+
+    let result = service
+        .call_tool(exec_lua("return vim.g.nvim_mcp_rejected == nil"))
+        .await?;
+    let text = result.content[0].as_text().expect("text content");
+    assert!(text.text.contains("true"));
+    assert!(messages.try_recv().is_ok());
+
+    // Tools without a confirmation run right away
+    let mut args = Map::new();
+    args.insert("connection_id".to_string(), Value::String(connection_id));
+    service
+        .call_tool(call_tool_req("list_buffers", Some(args)))
+        .await?;
+    assert!(messages.try_recv().is_err());
+
+    service.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use std::sync::{Arc, Mutex, OnceLock, PoisonError, RwLock};
+
+use rmcp::{
+    RoleServer,
+    model::{LoggingLevel, LoggingMessageNotificationParam},
+    service::Peer,
+};
+use serde_json::{Map, Value};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{Event, Level, Subscriber, field::Field};
+use tracing_subscriber::{
+    Layer,
+    filter::{FilterExt, LevelFilter, Targets},
+    layer::Context,
+    reload,
+};
+
+use super::core::NeovimMcpServer;
+
+/// Level forwarded to a peer that has not sent `logging/setLevel`
+pub const DEFAULT_LOG_LEVEL: LoggingLevel = LoggingLevel::Info;
+
+/// Log records buffered for each session before it lags
+const LOG_RECORD_CAPACITY: usize = 1024;
+
+/// A tracing event on its way to the MCP peers
+#[derive(Debug, Clone)]
+struct LogRecord {
+    level: LoggingLevel,
+    logger: String,
+    data: Value,
+    /// Target of the Neovim instance a message was sent by
+    nvim_target: Option<String>,
+}
+
+fn log_records() -> &'static broadcast::Sender<LogRecord> {
+    static LOG_RECORDS: OnceLock<broadcast::Sender<LogRecord>> = OnceLock::new();
+    LOG_RECORDS.get_or_init(|| broadcast::channel(LOG_RECORD_CAPACITY).0)
+}
+
+/// Levels of the sessions forwarding log messages
+fn session_levels() -> &'static Mutex<Vec<Arc<RwLock<LoggingLevel>>>> {
+    static SESSION_LEVELS: OnceLock<Mutex<Vec<Arc<RwLock<LoggingLevel>>>>> = OnceLock::new();
+    SESSION_LEVELS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Sets the level filter of the layer returned by [`mcp_logging_layer`]
+static RELOAD_FILTER: OnceLock<Box<dyn Fn(LevelFilter) + Send + Sync>> = OnceLock::new();
+
+fn level_filter(level: LoggingLevel) -> LevelFilter {
+    match level {
+        LoggingLevel::Debug => LevelFilter::DEBUG,
+        LoggingLevel::Info | LoggingLevel::Notice => LevelFilter::INFO,
+        LoggingLevel::Warning => LevelFilter::WARN,
+        _ => LevelFilter::ERROR,
+    }
+}
+
+/// Let through the events of the most verbose level a session asked for, and
+/// none while no session forwards log messages
+pub(crate) fn update_log_filter() {
+    let Some(reload_filter) = RELOAD_FILTER.get() else {
+        return;
+    };
+    let filter = session_levels()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .map(|level| level_filter(*level.read().unwrap_or_else(PoisonError::into_inner)))
+        .max()
+        .unwrap_or(LevelFilter::OFF);
+    reload_filter(filter);
+}
+
+/// Collects the fields of an event into a JSON object
+struct JsonVisitor(Map<String, Value>);
+
+impl tracing::field::Visit for JsonVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}").into());
+    }
+}
+
+/// Forwards the tracing events of this crate to MCP peers as log messages
+struct McpLoggingLayer;
+
+impl<S: Subscriber> Layer<S> for McpLoggingLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let sender = log_records();
+        if sender.receiver_count() == 0 {
+            return;
+        }
+
+        let metadata = event.metadata();
+        let mut visitor = JsonVisitor(Map::new());
+        event.record(&mut visitor);
+        let mut fields = visitor.0;
+        let nvim_target = match fields.remove("nvim_target") {
+            Some(Value::String(target)) => Some(target),
+            _ => None,
+        };
+        let level = match *metadata.level() {
+            Level::ERROR => LoggingLevel::Error,
+            Level::WARN => LoggingLevel::Warning,
+            Level::INFO => LoggingLevel::Info,
+            _ => LoggingLevel::Debug,
+        };
+        let _ = sender.send(LogRecord {
+            level,
+            logger: metadata.target().to_string(),
+            data: Value::Object(fields),
+            nvim_target,
+        });
+    }
+}
+
+/// Tracing layer that makes the events of nvim-mcp, including the messages of
+/// connected Neovim instances, available as MCP log messages.
+///
+/// Events of other crates are left out, since those of the MCP transport
+/// would otherwise be logged again while sending each message.
+///
+/// Events below the level the sessions asked for through `logging/setLevel`
+/// are filtered out before being recorded.
+pub fn mcp_logging_layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    let (level_filter, handle) = reload::Layer::new(LevelFilter::OFF);
+    // Only fails once the subscriber is gone, and logging it would recurse
+    let reload_filter = move |filter| {
+        let _ = handle.reload(filter);
+    };
+    if RELOAD_FILTER.set(Box::new(reload_filter)).is_ok() {
+        update_log_filter();
+    }
+    McpLoggingLayer.with_filter(
+        Targets::new()
+            .with_target("nvim_mcp", LevelFilter::DEBUG)
+            .and(level_filter),
+    )
+}
+
+/// Whether a message at `level` passes the `minimum` a peer asked for
+fn is_enabled(level: LoggingLevel, minimum: LoggingLevel) -> bool {
+    level as u8 >= minimum as u8
+}
+
+/// Send log messages to the peer at its requested level, until it disconnects.
+/// Messages of Neovim instances are only sent to the sessions connected to them.
+pub async fn forward_log_messages(server: NeovimMcpServer, peer: Peer<RoleServer>) {
+    let mut records = log_records().subscribe();
+    session_levels()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(server.log_level.clone());
+    update_log_filter();
+    loop {
+        let record = match records.recv().await {
+            Ok(record) => record,
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        };
+        if peer.is_transport_closed() {
+            break;
+        }
+        if !is_enabled(record.level, server.log_level()) {
+            continue;
+        }
+        if let Some(nvim_target) = &record.nvim_target
+            && !server
+                .nvim_clients
+                .iter()
+                .any(|entry| entry.value().target().as_ref() == Some(nvim_target))
+        {
+            continue;
+        }
+
+        let mut message = LoggingMessageNotificationParam::new(record.level, record.data);
+        message.logger = Some(record.logger);
+        // Logging the failure would be forwarded again, so stop quietly
+        if peer.notify_logging_message(message).await.is_err() {
+            break;
+        }
+    }
+    session_levels()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .retain(|level| !Arc::ptr_eq(level, &server.log_level));
+    update_log_filter();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_enabled() {
+        assert!(is_enabled(LoggingLevel::Error, LoggingLevel::Warning));
+        assert!(is_enabled(LoggingLevel::Warning, LoggingLevel::Warning));
+        assert!(!is_enabled(LoggingLevel::Info, LoggingLevel::Warning));
+        assert!(is_enabled(LoggingLevel::Debug, LoggingLevel::Debug));
+    }
+
+    #[test]
+    fn test_level_filter() {
+        assert_eq!(level_filter(LoggingLevel::Debug), LevelFilter::DEBUG);
+        assert_eq!(level_filter(LoggingLevel::Notice), LevelFilter::INFO);
+        assert_eq!(level_filter(LoggingLevel::Warning), LevelFilter::WARN);
+        assert_eq!(level_filter(LoggingLevel::Critical), LevelFilter::ERROR);
+    }
+}
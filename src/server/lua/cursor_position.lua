@@ -3,5 +3,7 @@ local buffer_name = vim.api.nvim_buf_get_name(buffer_id)
 local window_id = vim.api.nvim_get_current_win()
 local row, col = unpack(vim.api.nvim_win_get_cursor(window_id))
 -- row is one-indexed
--- col is zero-indexed
+-- col is a zero-indexed byte offset, reported in Unicode code points
+local line = vim.api.nvim_buf_get_lines(buffer_id, row - 1, row, false)[1] or ""
+col = vim.str_utfindex(line, "utf-32", math.min(col, #line), false)
 return { buffer_name = buffer_name, buffer_id = buffer_id, window_id = window_id, row = row - 1, col = col }
@@ -0,0 +1,47 @@
+local buffer_id = vim.api.nvim_get_current_buf()
+local window_id = vim.api.nvim_get_current_win()
+local row, col = unpack(vim.api.nvim_win_get_cursor(window_id))
+local current_line = vim.api.nvim_buf_get_lines(buffer_id, row - 1, row, false)[1] or ""
+
+-- The active visual selection, or the last one when not in visual mode
+local function get_selection()
+    local mode = vim.fn.mode()
+    local start_pos, end_pos, kind
+    if mode == "v" or mode == "V" or mode == "\22" then
+        start_pos, end_pos, kind = vim.fn.getpos("v"), vim.fn.getpos("."), mode
+    else
+        start_pos, end_pos, kind = vim.fn.getpos("'<"), vim.fn.getpos("'>"), vim.fn.visualmode()
+    end
+    if start_pos[2] == 0 or end_pos[2] == 0 then
+        return nil
+    end
+    if start_pos[2] > end_pos[2] or (start_pos[2] == end_pos[2] and start_pos[3] > end_pos[3]) then
+        start_pos, end_pos = end_pos, start_pos
+    end
+
+    local lines = vim.api.nvim_buf_get_lines(buffer_id, start_pos[2] - 1, end_pos[2], false)
+    if #lines == 0 then
+        return nil
+    end
+    if kind == "v" then
+        lines[#lines] = lines[#lines]:sub(1, end_pos[3])
+        lines[1] = lines[1]:sub(start_pos[3])
+    end
+    return {
+        start_line = start_pos[2] - 1,
+        end_line = end_pos[2] - 1,
+        text = table.concat(lines, "\n"),
+    }
+end
+
+return {
+    buffer_id = buffer_id,
+    buffer_name = vim.api.nvim_buf_get_name(buffer_id),
+    filetype = vim.bo[buffer_id].filetype,
+    -- Zero-based, the column in Unicode code points like the LSP tools
+    line = row - 1,
+    character = vim.str_utfindex(current_line, "utf-32", math.min(col, #current_line), false),
+    current_line = current_line,
+    word = vim.fn.expand("<cword>"),
+    selection = get_selection(),
+}
@@ -2,8 +2,12 @@ use std::collections::HashMap;
 
 use rmcp::{
     ErrorData as McpError,
-    model::{CallToolResult, Content},
+    model::{
+        CallToolResult, Content, GetPromptResult, Prompt, PromptArgument, PromptMessage,
+        PromptMessageRole, ReadResourceResult, ResourceContents, ToolAnnotations,
+    },
 };
+use serde::de::DeserializeOwned;
 use tracing::{debug, info, instrument, warn};
 
 use super::core::NeovimMcpServer;
@@ -16,10 +20,22 @@ pub struct LuaToolConfig {
     pub name: String,
     pub description: String,
     pub input_schema: serde_json::Value,
+    #[serde(default)]
+    pub annotations: Option<LuaToolAnnotations>,
     #[serde(skip)]
     validator: Option<LuaToolValidator>,
 }
 
+/// Behavior hints declared by a Lua tool in `setup()`
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct LuaToolAnnotations {
+    pub read_only: Option<bool>,
+    pub destructive: Option<bool>,
+    pub idempotent: Option<bool>,
+    pub open_world: Option<bool>,
+}
+
 impl LuaToolConfig {
     /// Create a new LuaToolConfig with validator
     #[allow(dead_code)]
@@ -33,6 +49,7 @@ impl LuaToolConfig {
             name,
             description,
             input_schema,
+            annotations: None,
             validator: Some(validator),
         })
     }
@@ -68,6 +85,17 @@ impl DynamicTool for LuaToolConfig {
         &self.input_schema
     }
 
+    fn annotations(&self) -> ToolAnnotations {
+        let hints = self.annotations.clone().unwrap_or_default();
+        ToolAnnotations::from_raw(
+            None,
+            hints.read_only,
+            hints.destructive,
+            hints.idempotent,
+            hints.open_world,
+        )
+    }
+
     /// Enhanced validation using LuaToolValidator
     fn validate_input(&self, arguments: &serde_json::Value) -> Result<(), rmcp::ErrorData> {
         match self.get_validator() {
@@ -330,6 +358,208 @@ fn convert_lua_response_to_mcp(lua_result: serde_json::Value) -> Result<CallTool
     Ok(CallToolResult::success(content))
 }
 
+/// Prompt registered through `custom_prompts` in the Lua plugin
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct LuaPromptConfig {
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub arguments: Vec<PromptArgument>,
+}
+
+impl LuaPromptConfig {
+    /// The MCP prompt, with an optional `connection_id` argument added to
+    /// pick the connection that renders it
+    pub fn to_prompt(&self) -> Prompt {
+        let mut arguments = self.arguments.clone();
+        if !arguments
+            .iter()
+            .any(|argument| argument.name == "connection_id")
+        {
+            arguments.push(
+                PromptArgument::new("connection_id")
+                    .with_description(
+                        "Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)",
+                    )
+                    .with_required(false),
+            );
+        }
+        Prompt::new(&self.name, Some(&self.description), Some(arguments))
+    }
+}
+
+/// Resource registered through `custom_resources` in the Lua plugin
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct LuaResourceConfig {
+    pub uri: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub mime_type: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct LuaPromptResponse {
+    description: Option<String>,
+    #[serde(default)]
+    messages: Vec<LuaPromptMessage>,
+    error: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct LuaPromptMessage {
+    role: PromptMessageRole,
+    text: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct LuaResourceResponse {
+    text: Option<String>,
+    mime_type: Option<String>,
+    error: Option<String>,
+}
+
+/// Call a discovery function of the Lua plugin, returning nothing when the
+/// plugin is missing or too old to provide it
+async fn discover_lua_registry<T: DeserializeOwned>(
+    client: &dyn NeovimClientTrait,
+    function: &str,
+) -> Result<HashMap<String, T>, NeovimError> {
+    let lua_code = format!(
+        r#"
+        local ok, nvim_mcp = pcall(require, 'nvim-mcp')
+        if not ok or nvim_mcp.{function} == nil then
+            return nil
+        end
+        return nvim_mcp.{function}()
+    "#
+    );
+    let result = client.execute_lua(&lua_code).await?;
+    let json_result = convert_nvim_value_to_json(result)?;
+    let registry: Option<HashMap<String, T>> = serde_json::from_value(json_result)
+        .map_err(|e| NeovimError::Api(format!("Failed to parse {function} result: {e}")))?;
+    Ok(registry.unwrap_or_default())
+}
+
+/// Discover prompts registered with `custom_prompts`
+#[instrument(skip(client))]
+pub async fn discover_lua_prompts(
+    client: &dyn NeovimClientTrait,
+) -> Result<HashMap<String, LuaPromptConfig>, NeovimError> {
+    let prompts = discover_lua_registry(client, "get_registered_prompts").await?;
+    debug!("Discovered {} Lua prompts", prompts.len());
+    Ok(prompts)
+}
+
+/// URI schemes of the built-in resources, which Lua resources cannot shadow
+const BUILTIN_URI_SCHEMES: &[&str] = &[
+    "nvim-connections://",
+    "nvim-tools://",
+    "nvim-diagnostics://",
+    "nvim-buffer://",
+    "nvim-file://",
+];
+
+fn is_builtin_resource_uri(uri: &str) -> bool {
+    BUILTIN_URI_SCHEMES
+        .iter()
+        .any(|scheme| uri.starts_with(scheme))
+}
+
+/// Discover resources registered with `custom_resources`, keyed by URI
+#[instrument(skip(client))]
+pub async fn discover_lua_resources(
+    client: &dyn NeovimClientTrait,
+) -> Result<HashMap<String, LuaResourceConfig>, NeovimError> {
+    let mut resources: HashMap<String, LuaResourceConfig> =
+        discover_lua_registry(client, "get_registered_resources").await?;
+    resources.retain(|uri, _| {
+        let builtin = is_builtin_resource_uri(uri);
+        if builtin {
+            warn!("Skipping Lua resource '{}' with a built-in URI scheme", uri);
+        }
+        !builtin
+    });
+    debug!("Discovered {} Lua resources", resources.len());
+    Ok(resources)
+}
+
+/// Render a Lua prompt with the given arguments
+#[instrument(skip(client))]
+pub async fn get_lua_prompt(
+    client: &dyn NeovimClientTrait,
+    prompt: &LuaPromptConfig,
+    arguments: &serde_json::Map<String, serde_json::Value>,
+) -> Result<GetPromptResult, McpError> {
+    for argument in &prompt.arguments {
+        if argument.required == Some(true) && !arguments.contains_key(&argument.name) {
+            return Err(McpError::invalid_params(
+                format!(
+                    "Missing required argument '{}' for prompt '{}'",
+                    argument.name, prompt.name
+                ),
+                None,
+            ));
+        }
+    }
+
+    let code = format!(
+        "return require('nvim-mcp').get_prompt({:?}, vim.json.decode({:?}))",
+        prompt.name,
+        serde_json::to_string(arguments).unwrap_or_default()
+    );
+    let result = client.execute_lua(&code).await.map_err(|e| {
+        McpError::internal_error(
+            format!("Failed to render Lua prompt '{}': {}", prompt.name, e),
+            None,
+        )
+    })?;
+    let json_result = convert_nvim_value_to_json(result)
+        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+    let response: LuaPromptResponse = serde_json::from_value(json_result).map_err(|e| {
+        McpError::internal_error(format!("Failed to parse Lua prompt: {}", e), None)
+    })?;
+    if let Some(error) = response.error {
+        return Err(McpError::invalid_request(error, None));
+    }
+
+    let messages = response
+        .messages
+        .into_iter()
+        .map(|message| PromptMessage::new_text(message.role, message.text))
+        .collect();
+    let mut result = GetPromptResult::new(messages);
+    if let Some(description) = response.description {
+        result = result.with_description(description);
+    }
+    Ok(result)
+}
+
+/// Read a Lua resource
+#[instrument(skip(client))]
+pub async fn read_lua_resource(
+    client: &dyn NeovimClientTrait,
+    uri: &str,
+) -> Result<ReadResourceResult, McpError> {
+    let code = format!("return require('nvim-mcp').read_resource({:?})", uri);
+    let result = client.execute_lua(&code).await.map_err(|e| {
+        McpError::internal_error(format!("Failed to read Lua resource '{uri}': {e}"), None)
+    })?;
+    let json_result = convert_nvim_value_to_json(result)
+        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+    let response: LuaResourceResponse = serde_json::from_value(json_result).map_err(|e| {
+        McpError::internal_error(format!("Failed to parse Lua resource: {}", e), None)
+    })?;
+    if let Some(error) = response.error {
+        return Err(McpError::internal_error(error, None));
+    }
+
+    let mut contents = ResourceContents::text(response.text.unwrap_or_default(), uri);
+    if let Some(mime_type) = response.mime_type {
+        contents = contents.with_mime_type(mime_type);
+    }
+    Ok(ReadResourceResult::new(vec![contents]))
+}
+
 // Helper function to register discovered Lua tools as dynamic tools
 #[instrument(skip(server, client))]
 pub async fn discover_and_register_lua_tools(
@@ -352,6 +582,17 @@ pub async fn discover_and_register_lua_tools(
         );
     }
 
+    // Prompts and resources registered with the plugin, which do not hold up
+    // the tools when they fail to load
+    match discover_lua_prompts(client).await {
+        Ok(prompts) => server.register_lua_prompts(connection_id, prompts),
+        Err(e) => warn!("Failed to discover Lua prompts: {}", e),
+    }
+    match discover_lua_resources(client).await {
+        Ok(resources) => server.register_lua_resources(connection_id, resources),
+        Err(e) => warn!("Failed to discover Lua resources: {}", e),
+    }
+
     debug!(
         "Completed Lua tool registration for connection: {}",
         connection_id
@@ -364,6 +605,13 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn test_is_builtin_resource_uri() {
+        assert!(is_builtin_resource_uri("nvim-buffer://abc1234/1"));
+        assert!(is_builtin_resource_uri("nvim-connections://"));
+        assert!(!is_builtin_resource_uri("nvim-todo://list"));
+    }
+
     #[test]
     fn test_lua_tool_validator_creation() {
         let schema = json!({
@@ -520,6 +768,7 @@ mod tests {
                 },
                 "required": ["message"]
             }),
+            annotations: None,
             validator: None,
         };
 
@@ -587,4 +836,47 @@ mod tests {
             assert!(error.to_string().contains("Invalid JSON schema"));
         }
     }
+
+    #[test]
+    fn test_lua_prompt_config_adds_connection_id() {
+        let config: LuaPromptConfig = serde_json::from_value(json!({
+            "name": "summarize",
+            "description": "Summarize a buffer",
+            "arguments": [{ "name": "focus", "required": true }]
+        }))
+        .unwrap();
+
+        let prompt = config.to_prompt();
+        let arguments = prompt.arguments.unwrap();
+        assert_eq!(arguments.len(), 2);
+        assert_eq!(arguments[0].name, "focus");
+        assert_eq!(arguments[0].required, Some(true));
+        assert_eq!(arguments[1].name, "connection_id");
+        assert_eq!(arguments[1].required, Some(false));
+    }
+
+    #[test]
+    fn test_lua_tool_config_annotations() {
+        let config: LuaToolConfig = serde_json::from_value(json!({
+            "name": "buffer_stats",
+            "description": "Count lines of a buffer",
+            "input_schema": { "type": "object" },
+            "annotations": { "read_only": true, "open_world": false }
+        }))
+        .unwrap();
+        let annotations = DynamicTool::annotations(&config);
+        assert_eq!(annotations.read_only_hint, Some(true));
+        assert_eq!(annotations.open_world_hint, Some(false));
+        assert_eq!(annotations.destructive_hint, None);
+
+        // An empty Lua table is encoded as an empty array
+        let config: LuaToolConfig = serde_json::from_value(json!({
+            "name": "buffer_stats",
+            "description": "Count lines of a buffer",
+            "input_schema": { "type": "object" },
+            "annotations": []
+        }))
+        .unwrap();
+        assert_eq!(DynamicTool::annotations(&config), ToolAnnotations::new());
+    }
 }
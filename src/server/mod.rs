@@ -1,6 +1,11 @@
+mod completions;
+pub mod confirmation;
 pub mod core;
 mod hybrid_router;
+pub(crate) mod logging;
 pub(crate) mod lua_tools;
+mod output;
+mod prompts;
 mod resources;
 pub(crate) mod tools;
 
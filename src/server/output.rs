@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use rmcp::{
+    ErrorData as McpError,
+    handler::server::tool::schema_for_output,
+    model::{CallToolResult, Content, JsonObject},
+};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// Wraps tool results whose schema is not an object, as the MCP specification
+/// requires `structuredContent` to be a JSON object
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ToolOutput<T> {
+    pub result: T,
+}
+
+/// Result of a tool that can run on every connection at once
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+#[serde(untagged)]
+pub enum Broadcast<T> {
+    /// Result of the requested connection
+    Single(T),
+    /// Results keyed by connection ID
+    AllConnections(BTreeMap<String, ConnectionResult<T>>),
+}
+
+/// Result of a broadcast tool on one connection
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+#[serde(untagged)]
+pub enum ConnectionResult<T> {
+    Ok(T),
+    Err { error: String },
+}
+
+/// Result of the LSP client a tool picked, whose name the follow-up tools
+/// taking `lsp_client_name` need
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct LspClientOutput<T> {
+    /// Name of the LSP client that returned the result
+    pub lsp_client_name: String,
+    pub result: T,
+}
+
+/// Edits or actions returned for inspection, or a message describing what
+/// was done with them
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+#[serde(untagged)]
+pub enum EditsOrMessage<T> {
+    Edits(T),
+    Message(String),
+}
+
+/// Output schema of a tool returning `T`, wrapped in [`ToolOutput`] unless
+/// `T` is already an object
+pub fn output_schema<T: JsonSchema + 'static>() -> Arc<JsonObject> {
+    schema_for_output::<T>().unwrap_or_else(|_| {
+        schema_for_output::<ToolOutput<T>>().expect("ToolOutput schema is an object")
+    })
+}
+
+/// Build a successful tool result carrying `value` as structured content that
+/// matches [`output_schema`]. The text content is kept for clients without
+/// structured output support: strings as is, anything else as JSON.
+pub fn structured_result<T: Serialize + JsonSchema + 'static>(
+    value: T,
+) -> Result<CallToolResult, McpError> {
+    let structured = serde_json::to_value(&value)
+        .map_err(|e| McpError::internal_error(format!("Failed to serialize result: {e}"), None))?;
+    let content = match &structured {
+        serde_json::Value::String(text) => Content::text(text.clone()),
+        _ => Content::json(&value)?,
+    };
+    let structured = if schema_for_output::<T>().is_ok() {
+        structured
+    } else {
+        serde_json::json!({ "result": structured })
+    };
+    let mut result = CallToolResult::success(vec![content]);
+    result.structured_content = Some(structured);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neovim::client::BufferInfo;
+
+    #[test]
+    fn test_output_schema_is_object() {
+        assert_eq!(
+            output_schema::<Vec<BufferInfo>>()
+                .get("type")
+                .and_then(|t| t.as_str()),
+            Some("object")
+        );
+        assert_eq!(
+            output_schema::<Broadcast<Vec<BufferInfo>>>()
+                .get("type")
+                .and_then(|t| t.as_str()),
+            Some("object")
+        );
+    }
+
+    #[test]
+    fn test_structured_result() {
+        let result = structured_result(ToolOutput { result: 1 }).unwrap();
+        assert_eq!(
+            result.structured_content,
+            Some(serde_json::json!({ "result": 1 }))
+        );
+
+        let result = structured_result(vec![1, 2]).unwrap();
+        assert_eq!(
+            result.structured_content,
+            Some(serde_json::json!({ "result": [1, 2] }))
+        );
+
+        let result = structured_result("done".to_string()).unwrap();
+        assert_eq!(
+            result.structured_content,
+            Some(serde_json::json!({ "result": "done" }))
+        );
+        assert_eq!(result.content[0].as_text().unwrap().text, "done");
+    }
+}
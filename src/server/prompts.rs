@@ -0,0 +1,209 @@
+use rmcp::{
+    ErrorData as McpError,
+    handler::server::{router::prompt::PromptRouter, wrapper::Parameters},
+    model::*,
+    prompt, prompt_router,
+};
+use tracing::instrument;
+
+use super::core::NeovimMcpServer;
+use super::lua_tools;
+use super::tools::ConnectionRequest;
+use crate::neovim::{NeovimClientTrait, client::Diagnostic};
+
+/// Editor state a prompt is rendered from
+#[derive(Debug, serde::Deserialize)]
+struct EditorContext {
+    buffer_id: u64,
+    buffer_name: String,
+    filetype: String,
+    line: u64,
+    character: u64,
+    current_line: String,
+    word: String,
+    selection: Option<Selection>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct Selection {
+    start_line: u64,
+    end_line: u64,
+    text: String,
+}
+
+impl EditorContext {
+    fn buffer_label(&self) -> String {
+        if self.buffer_name.is_empty() {
+            format!("buffer {}", self.buffer_id)
+        } else {
+            format!("`{}` (buffer {})", self.buffer_name, self.buffer_id)
+        }
+    }
+}
+
+async fn editor_context(client: &dyn NeovimClientTrait) -> Result<EditorContext, McpError> {
+    let result = client
+        .execute_lua(include_str!("lua/prompt_context.lua"))
+        .await?;
+    let value = lua_tools::convert_nvim_value_to_json(result).map_err(|e| {
+        McpError::internal_error(format!("Failed to read editor context: {e}"), None)
+    })?;
+    serde_json::from_value(value)
+        .map_err(|e| McpError::internal_error(format!("Failed to parse editor context: {e}"), None))
+}
+
+fn severity_name(severity: u8) -> &'static str {
+    match severity {
+        1 => "error",
+        2 => "warning",
+        3 => "info",
+        _ => "hint",
+    }
+}
+
+fn format_diagnostics(diagnostics: &[Diagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(|d| {
+            format!(
+                "- {}:{} {} [{}]: {}",
+                d.lnum,
+                d.col,
+                severity_name(d.severity),
+                d.source,
+                d.message
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[prompt_router]
+impl NeovimMcpServer {
+    #[prompt(
+        name = "fix_diagnostics",
+        description = "Fix the diagnostics reported in the current buffer"
+    )]
+    #[instrument(skip(self))]
+    pub async fn fix_diagnostics_prompt(
+        &self,
+        Parameters(ConnectionRequest { connection_id }): Parameters<ConnectionRequest>,
+    ) -> Result<GetPromptResult, McpError> {
+        let connection_id = self.resolve_connection_id(connection_id.as_deref())?;
+        let client = self.get_connection(Some(&connection_id))?;
+        let context = editor_context(&**client).await?;
+        let diagnostics = client.get_buffer_diagnostics(context.buffer_id).await?;
+
+        let text = if diagnostics.is_empty() {
+            format!(
+                "There are no diagnostics in {} of Neovim connection `{connection_id}`. \
+                 Check whether a language server is attached with `lsp_clients` and report \
+                 anything that looks wrong.",
+                context.buffer_label()
+            )
+        } else {
+            format!(
+                "Fix the diagnostics in {} ({}) of Neovim connection `{connection_id}`. \
+                 Positions are zero-based line:character.\n\n{}\n\n\
+                 Read the affected code first. Prefer the language server's code actions \
+                 (`lsp_code_actions`, `lsp_resolve_code_action`, `lsp_apply_edit`) over manual \
+                 edits, and explain each fix.",
+                context.buffer_label(),
+                context.filetype,
+                format_diagnostics(&diagnostics)
+            )
+        };
+        Ok(
+            GetPromptResult::new(vec![PromptMessage::new_text(PromptMessageRole::User, text)])
+                .with_description(format!(
+                    "{} diagnostics in {}",
+                    diagnostics.len(),
+                    context.buffer_label()
+                )),
+        )
+    }
+
+    #[prompt(
+        name = "explain_symbol",
+        description = "Explain the symbol under the cursor"
+    )]
+    #[instrument(skip(self))]
+    pub async fn explain_symbol_prompt(
+        &self,
+        Parameters(ConnectionRequest { connection_id }): Parameters<ConnectionRequest>,
+    ) -> Result<GetPromptResult, McpError> {
+        let connection_id = self.resolve_connection_id(connection_id.as_deref())?;
+        let client = self.get_connection(Some(&connection_id))?;
+        let context = editor_context(&**client).await?;
+        if context.word.is_empty() {
+            return Err(McpError::invalid_request(
+                "No symbol under the cursor",
+                None,
+            ));
+        }
+
+        let text = format!(
+            "Explain the symbol `{}` under the cursor in {} of Neovim connection \
+             `{connection_id}`, at line {} character {} (zero-based):\n\n```{}\n{}\n```\n\n\
+             Use `lsp_hover` and `lsp_definition` at that position to find what it is and \
+             where it is defined, and `lsp_references` to see how it is used. Describe its \
+             purpose, type and behaviour.",
+            context.word,
+            context.buffer_label(),
+            context.line,
+            context.character,
+            context.filetype,
+            context.current_line
+        );
+        Ok(
+            GetPromptResult::new(vec![PromptMessage::new_text(PromptMessageRole::User, text)])
+                .with_description(format!("Explain `{}`", context.word)),
+        )
+    }
+
+    #[prompt(
+        name = "review_selection",
+        description = "Review the visual selection in the current buffer"
+    )]
+    #[instrument(skip(self))]
+    pub async fn review_selection_prompt(
+        &self,
+        Parameters(ConnectionRequest { connection_id }): Parameters<ConnectionRequest>,
+    ) -> Result<GetPromptResult, McpError> {
+        let connection_id = self.resolve_connection_id(connection_id.as_deref())?;
+        let client = self.get_connection(Some(&connection_id))?;
+        let context = editor_context(&**client).await?;
+        let Some(selection) = &context.selection else {
+            return Err(McpError::invalid_request(
+                "No visual selection in the current buffer",
+                None,
+            ));
+        };
+
+        let text = format!(
+            "Review this code selected in {} of Neovim connection `{connection_id}`, \
+             lines {} to {} (zero-based):\n\n```{}\n{}\n```\n\n\
+             Point out bugs, unclear naming, missing error handling and simpler \
+             alternatives. Use the LSP tools for context on the symbols it uses.",
+            context.buffer_label(),
+            selection.start_line,
+            selection.end_line,
+            context.filetype,
+            selection.text
+        );
+        Ok(
+            GetPromptResult::new(vec![PromptMessage::new_text(PromptMessageRole::User, text)])
+                .with_description(format!(
+                    "Review lines {} to {} of {}",
+                    selection.start_line,
+                    selection.end_line,
+                    context.buffer_label()
+                )),
+        )
+    }
+}
+
+/// Build prompt router for NeovimMcpServer
+pub fn build_prompt_router() -> PromptRouter<NeovimMcpServer> {
+    NeovimMcpServer::prompt_router()
+}
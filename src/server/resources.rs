@@ -1,13 +1,170 @@
 use regex::Regex;
 use rmcp::{
     ErrorData as McpError, ServerHandler,
+    handler::server::prompt::PromptContext,
     model::*,
-    service::{RequestContext, RoleServer},
+    service::{NotificationContext, RequestContext, RoleServer},
 };
 use serde_json::json;
 use tracing::{debug, info, instrument};
 
-use super::core::NeovimMcpServer;
+use super::core::{NeovimMcpServer, describe_connection, watch_project_targets};
+use super::logging::forward_log_messages;
+use super::lua_tools;
+use crate::neovim::DocumentIdentifier;
+
+/// Upper bound for listing the buffers of a connection
+const BUFFER_LIST_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// MIME types of common filetypes, with the file extensions they are used for
+const MIME_TYPES: &[(&str, &[&str], &str)] = &[
+    ("c", &["c", "h"], "text/x-c"),
+    ("cpp", &["cpp", "cc", "cxx", "hpp", "hh"], "text/x-c++"),
+    ("css", &["css"], "text/css"),
+    ("go", &["go"], "text/x-go"),
+    ("html", &["html", "htm"], "text/html"),
+    ("java", &["java"], "text/x-java"),
+    ("javascript", &["js", "mjs", "cjs"], "text/javascript"),
+    ("json", &["json"], "application/json"),
+    ("lua", &["lua"], "text/x-lua"),
+    ("markdown", &["md", "markdown"], "text/markdown"),
+    ("python", &["py"], "text/x-python"),
+    ("rust", &["rs"], "text/x-rust"),
+    ("sh", &["sh", "bash"], "application/x-sh"),
+    ("sql", &["sql"], "application/sql"),
+    ("toml", &["toml"], "application/toml"),
+    ("typescript", &["ts", "mts", "cts"], "text/x-typescript"),
+    ("xml", &["xml"], "application/xml"),
+    ("yaml", &["yaml", "yml"], "application/yaml"),
+];
+
+fn mime_type_for_filetype(filetype: &str) -> &'static str {
+    MIME_TYPES
+        .iter()
+        .find(|(name, _, _)| *name == filetype)
+        .map_or("text/plain", |(_, _, mime_type)| mime_type)
+}
+
+fn mime_type_for_path(path: &std::path::Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    MIME_TYPES
+        .iter()
+        .find(|(_, extensions, _)| extensions.contains(&extension))
+        .map_or("text/plain", |(_, _, mime_type)| mime_type)
+}
+
+/// Decode the path of a `nvim-file://` URI, which must stay inside the project root
+fn project_relative_path(encoded: &str) -> Option<std::path::PathBuf> {
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut rest = encoded.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    let path = std::path::PathBuf::from(String::from_utf8(bytes).ok()?);
+    let inside_project = path.components().all(|component| {
+        matches!(
+            component,
+            std::path::Component::Normal(_) | std::path::Component::CurDir
+        )
+    });
+    (inside_project && path.file_name().is_some()).then_some(path)
+}
+
+fn new_resource_template(
+    uri_template: &str,
+    name: &str,
+    description: &str,
+    mime_type: Option<&str>,
+) -> ResourceTemplate {
+    let template = RawResourceTemplate::new(uri_template, name).with_description(description);
+    match mime_type {
+        Some(mime_type) => template.with_mime_type(mime_type),
+        None => template,
+    }
+    .no_annotation()
+}
+
+/// Templates of the resources of each connection
+pub(crate) fn resource_templates() -> Vec<ResourceTemplate> {
+    vec![
+        new_resource_template(
+            "nvim-buffer://{connection_id}/{buffer_id}",
+            "Buffer Contents",
+            "Contents of a loaded buffer, including unsaved changes",
+            None,
+        ),
+        new_resource_template(
+            "nvim-file://{connection_id}/{path}",
+            "Project File",
+            "A file by its path relative to the project root, read from its buffer when loaded",
+            None,
+        ),
+        new_resource_template(
+            "nvim-diagnostics://{connection_id}/workspace",
+            "Workspace Diagnostics",
+            "Diagnostic messages across the workspace of a connection",
+            Some("application/json"),
+        ),
+        new_resource_template(
+            "nvim-diagnostics://{connection_id}/buffer/{buffer_id}",
+            "Buffer Diagnostics",
+            "Diagnostic messages for a buffer of a connection",
+            Some("application/json"),
+        ),
+        new_resource_template(
+            "nvim-tools://{connection_id}",
+            "Tools for Connection",
+            "List of tools available for a connection",
+            Some("application/json"),
+        ),
+    ]
+}
+
+/// The loaded buffers of a connection that show up in its buffer list
+async fn buffer_resources(server: &NeovimMcpServer, connection_id: &str) -> Vec<Resource> {
+    let Ok(client) = server.get_connection(Some(connection_id)) else {
+        return vec![];
+    };
+    let buffers = match tokio::time::timeout(BUFFER_LIST_TIMEOUT, client.get_buffers()).await {
+        Ok(Ok(buffers)) => buffers,
+        _ => {
+            debug!("Failed to list buffers for connection '{connection_id}'");
+            return vec![];
+        }
+    };
+    buffers
+        .into_iter()
+        .filter(|buffer| buffer.listed && buffer.loaded)
+        .map(|buffer| {
+            let label = if buffer.name.is_empty() {
+                format!("Buffer {}", buffer.id)
+            } else {
+                buffer.name.clone()
+            };
+            let mut resource = new_resource(
+                &format!("nvim-buffer://{connection_id}/{}", buffer.id),
+                &format!("{label} ({connection_id})"),
+                Some(&format!(
+                    "Contents of buffer {} for connection {connection_id}",
+                    buffer.id
+                )),
+            );
+            resource.raw.mime_type = Some(mime_type_for_filetype(&buffer.filetype).to_string());
+            resource.raw.size = u32::try_from(buffer.size).ok();
+            resource
+        })
+        .collect()
+}
 
 fn new_resource(uri: &str, name: &str, description: Option<&str>) -> Resource {
     Resource {
@@ -34,10 +191,24 @@ impl ServerHandler for NeovimMcpServer {
             .enable_tools()
             .enable_tool_list_changed()
             .enable_resources()
+            .enable_resources_subscribe()
+            .enable_prompts()
+            .enable_completions()
+            .enable_logging()
             .build();
         info
     }
 
+    #[instrument(skip(self, context))]
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        info!("Client initialized");
+        tokio::spawn(forward_log_messages(self.clone(), context.peer.clone()));
+        // Pick up Neovim instances started or closed after startup
+        if self.connect_mode.as_deref() == Some("auto") {
+            tokio::spawn(watch_project_targets(self.clone(), context.peer));
+        }
+    }
+
     #[instrument(skip(self))]
     async fn list_resources(
         &self,
@@ -82,6 +253,32 @@ impl ServerHandler for NeovimMcpServer {
             ));
         }
 
+        // Add the open buffers of each connection
+        let connection_ids: Vec<String> = self
+            .nvim_clients
+            .iter()
+            .filter(|entry| !entry.value().is_stale())
+            .map(|entry| entry.key().clone())
+            .collect();
+        let buffers = futures::future::join_all(
+            connection_ids
+                .iter()
+                .map(|connection_id| buffer_resources(self, connection_id)),
+        )
+        .await;
+        resources.extend(buffers.into_iter().flatten());
+
+        // Add resources registered by the Lua plugin
+        for lua_resource in self.list_lua_resources() {
+            let mut resource = new_resource(
+                &lua_resource.uri,
+                &lua_resource.name,
+                lua_resource.description.as_deref(),
+            );
+            resource.raw.mime_type = Some(lua_resource.mime_type);
+            resources.push(resource);
+        }
+
         Ok(ListResourcesResult {
             resources,
             next_cursor: None,
@@ -89,6 +286,17 @@ impl ServerHandler for NeovimMcpServer {
         })
     }
 
+    #[instrument(skip(self))]
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, McpError> {
+        Ok(ListResourceTemplatesResult::with_all_items(
+            resource_templates(),
+        ))
+    }
+
     #[instrument(skip(self))]
     async fn read_resource(
         &self,
@@ -99,17 +307,17 @@ impl ServerHandler for NeovimMcpServer {
 
         match uri.as_str() {
             "nvim-connections://" => {
-                let connections: Vec<_> = self
+                let connection_ids: Vec<String> = self
                     .nvim_clients
                     .iter()
-                    .map(|entry| {
-                        json!({
-                            "id": entry.key(),
-                            "target": entry.value().target()
-                                .unwrap_or_else(|| "Unknown".to_string())
-                        })
-                    })
+                    .map(|entry| entry.key().clone())
                     .collect();
+                let connections = futures::future::join_all(
+                    connection_ids
+                        .iter()
+                        .map(|connection_id| describe_connection(self, connection_id)),
+                )
+                .await;
 
                 Ok(ReadResourceResult::new(vec![ResourceContents::text(
                     serde_json::to_string_pretty(&connections).map_err(|e| {
@@ -184,7 +392,7 @@ impl ServerHandler for NeovimMcpServer {
                 }
 
                 // Verify connection exists
-                let _client = self.get_connection(connection_id)?;
+                let _client = self.get_connection(Some(connection_id))?;
 
                 // Get clean tools info for this connection
                 let tools_info_data = self.hybrid_router.get_connection_tools_info(connection_id);
@@ -231,7 +439,8 @@ impl ServerHandler for NeovimMcpServer {
                     let connection_id = captures.get(1).unwrap().as_str();
                     let resource_type = captures.get(2).unwrap().as_str();
 
-                    let client = self.get_connection(connection_id)?;
+                    self.ensure_connection_alive(connection_id).await?;
+                    let client = self.get_connection(Some(connection_id))?;
 
                     match resource_type {
                         "workspace" => {
@@ -277,13 +486,198 @@ impl ServerHandler for NeovimMcpServer {
                     ))
                 }
             }
-            _ => Err(McpError::resource_not_found(
-                "resource_not_found",
-                Some(json!({"uri": uri})),
-            )),
+            uri if uri.starts_with("nvim-buffer://") => {
+                let (connection_id, buffer_id) = uri
+                    .strip_prefix("nvim-buffer://")
+                    .and_then(|rest| rest.split_once('/'))
+                    .and_then(|(connection_id, buffer_id)| {
+                        Some((connection_id, buffer_id.parse::<u64>().ok()?))
+                    })
+                    .ok_or_else(|| {
+                        McpError::invalid_params(
+                            "Invalid buffer URI, expected nvim-buffer://{connection_id}/{buffer_id}",
+                            None,
+                        )
+                    })?;
+
+                self.ensure_connection_alive(connection_id).await?;
+                let client = self.get_connection(Some(connection_id))?;
+                let Some(buffer) = client
+                    .get_buffers()
+                    .await?
+                    .into_iter()
+                    .find(|buffer| buffer.id == buffer_id && buffer.loaded)
+                else {
+                    return Err(McpError::resource_not_found(
+                        "resource_not_found",
+                        Some(json!({"uri": uri})),
+                    ));
+                };
+
+                let text = client
+                    .read_document(DocumentIdentifier::BufferId(buffer_id), 0, -1)
+                    .await?;
+                Ok(ReadResourceResult::new(vec![
+                    ResourceContents::text(text, uri)
+                        .with_mime_type(mime_type_for_filetype(&buffer.filetype)),
+                ]))
+            }
+            uri if uri.starts_with("nvim-file://") => {
+                let (connection_id, path) = uri
+                    .strip_prefix("nvim-file://")
+                    .and_then(|rest| rest.split_once('/'))
+                    .ok_or_else(|| {
+                        McpError::invalid_params(
+                            "Invalid file URI, expected nvim-file://{connection_id}/{path}",
+                            None,
+                        )
+                    })?;
+                let path = project_relative_path(path).ok_or_else(|| {
+                    McpError::invalid_params(
+                        "File path must be relative to the project root",
+                        Some(json!({"uri": uri})),
+                    )
+                })?;
+
+                self.ensure_connection_alive(connection_id).await?;
+                let client = self.get_connection(Some(connection_id))?;
+                let absolute_path =
+                    std::path::Path::new(&client.get_instance_info().await?.cwd).join(&path);
+
+                // Prefer the buffer of a loaded file, which has any unsaved changes
+                let buffers = client.get_buffers().await?;
+                let (document, mime_type) = match buffers.iter().find(|buffer| {
+                    buffer.loaded && std::path::Path::new(&buffer.name) == absolute_path
+                }) {
+                    Some(buffer) => (
+                        DocumentIdentifier::BufferId(buffer.id),
+                        mime_type_for_filetype(&buffer.filetype),
+                    ),
+                    None => (
+                        DocumentIdentifier::ProjectRelativePath(path.clone()),
+                        mime_type_for_path(&path),
+                    ),
+                };
+
+                let text = client.read_document(document, 0, -1).await.map_err(|e| {
+                    McpError::resource_not_found(
+                        "resource_not_found",
+                        Some(json!({"uri": uri, "error": e.to_string()})),
+                    )
+                })?;
+                Ok(ReadResourceResult::new(vec![
+                    ResourceContents::text(text, uri).with_mime_type(mime_type),
+                ]))
+            }
+            uri => {
+                let Some(connection_id) = self.lua_resource_connection(uri) else {
+                    return Err(McpError::resource_not_found(
+                        "resource_not_found",
+                        Some(json!({"uri": uri})),
+                    ));
+                };
+                self.ensure_connection_alive(&connection_id).await?;
+                let client = self.get_connection(Some(&connection_id))?;
+                lua_tools::read_lua_resource(&**client, uri).await
+            }
         }
     }
 
+    #[instrument(skip(self, context))]
+    async fn subscribe(
+        &self,
+        request: SubscribeRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        debug!("Subscribing to resource: {}", request.uri);
+        self.subscribe_resource(&request.uri, context.peer)
+    }
+
+    #[instrument(skip(self, _context))]
+    async fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        debug!("Unsubscribing from resource: {}", request.uri);
+        self.unsubscribe_resource(&request.uri);
+        Ok(())
+    }
+
+    #[instrument(skip(self, _context))]
+    async fn set_level(
+        &self,
+        request: SetLevelRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        debug!("Setting log level to {:?}", request.level);
+        self.set_log_level(request.level);
+        Ok(())
+    }
+
+    #[instrument(skip(self, _context))]
+    async fn complete(
+        &self,
+        request: CompleteRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CompleteResult, McpError> {
+        Ok(CompleteResult::new(self.complete_argument(&request).await?))
+    }
+
+    #[instrument(skip(self))]
+    async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, McpError> {
+        let mut prompts = self.prompt_router.list_all();
+        prompts.extend(self.list_lua_prompts());
+        Ok(ListPromptsResult {
+            prompts,
+            next_cursor: None,
+            meta: None,
+        })
+    }
+
+    #[instrument(skip(self, context))]
+    async fn get_prompt(
+        &self,
+        GetPromptRequestParams {
+            name, arguments, ..
+        }: GetPromptRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, McpError> {
+        debug!("Rendering prompt: {}", name);
+        let connection_id = arguments
+            .as_ref()
+            .and_then(|args| args.get("connection_id"))
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .or_else(|| self.default_connection_id());
+        if let Some(connection_id) = &connection_id {
+            self.ensure_connection_alive(connection_id).await?;
+        }
+        if self.prompt_router.has_route(&name) {
+            return self
+                .prompt_router
+                .get_prompt(PromptContext::new(self, name, arguments, context))
+                .await;
+        }
+
+        // Prompts registered by the Lua plugin of the connection
+        let connection_id = self.resolve_connection_id(connection_id.as_deref())?;
+        let Some(prompt) = self.get_lua_prompt(&connection_id, &name) else {
+            return Err(McpError::invalid_params(
+                format!("Prompt '{name}' not found on connection '{connection_id}'"),
+                None,
+            ));
+        };
+        let mut arguments = arguments.unwrap_or_default();
+        arguments.remove("connection_id");
+        let client = self.get_connection(Some(&connection_id))?;
+        lua_tools::get_lua_prompt(&**client, &prompt, &arguments).await
+    }
+
     // Override list_tools to use HybridToolRouter
     #[instrument(skip(self))]
     async fn list_tools(
@@ -342,6 +736,9 @@ impl ServerHandler for NeovimMcpServer {
         context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
         debug!("Calling tool: {} via HybridToolRouter", name);
+        let Some(_call) = self.shutdown_coordinator().begin_call() else {
+            return Err(McpError::internal_error("Server is shutting down", None));
+        };
 
         // Convert arguments to serde_json::Value
         let args = arguments.unwrap_or_default();
@@ -352,9 +749,99 @@ impl ServerHandler for NeovimMcpServer {
             )
         })?;
 
+        // Revive stale connections before dispatching to them
+        let connection_id = match args_value.get("connection_id").and_then(|v| v.as_str()) {
+            Some(connection_id) => Some(connection_id.to_string()),
+            None => self.default_connection_id(),
+        };
+        if let Some(connection_id) = &connection_id
+            && self.ensure_connection_alive(connection_id).await?
+        {
+            context
+                .peer
+                .notify_tool_list_changed()
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Failed to notify tool list changed: {}", e);
+                });
+        }
+
         // Use HybridToolRouter for dispatch
-        self.hybrid_router
+        let result = self
+            .hybrid_router
             .call_tool(self, &name, args_value, context)
-            .await
+            .await?;
+
+        // Enforce the connection's response size limit on read-only tools, the
+        // changes of a mutating tool are already made when it returns
+        if !self
+            .hybrid_router
+            .is_read_only(&name, connection_id.as_deref())
+        {
+            return Ok(result);
+        }
+        let max_response_bytes = connection_id.as_ref().and_then(|connection_id| {
+            self.nvim_clients
+                .get(connection_id)
+                .and_then(|client| client.config().max_response_bytes)
+        });
+        if let Some(max_response_bytes) = max_response_bytes {
+            let size: usize = result
+                .content
+                .iter()
+                .filter_map(|content| content.as_text())
+                .map(|text| text.text.len())
+                .sum::<usize>()
+                + result
+                    .structured_content
+                    .as_ref()
+                    .map_or(0, |structured| structured.to_string().len());
+            if size > max_response_bytes {
+                return Err(McpError::invalid_request(
+                    format!(
+                        "Response of {size} bytes exceeds max_response_bytes ({max_response_bytes}), narrow the request"
+                    ),
+                    None,
+                ));
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_relative_path() {
+        assert_eq!(
+            project_relative_path("src/main.rs"),
+            Some("src/main.rs".into())
+        );
+        assert_eq!(
+            project_relative_path("docs/my%20notes.md"),
+            Some("docs/my notes.md".into())
+        );
+        assert_eq!(project_relative_path("../secret"), None);
+        assert_eq!(project_relative_path("src/%2E%2E/%2E%2E/secret"), None);
+        assert_eq!(project_relative_path("/etc/passwd"), None);
+        assert_eq!(project_relative_path("%2Fetc/passwd"), None);
+        assert_eq!(project_relative_path("src/%zz"), None);
+        assert_eq!(project_relative_path(""), None);
+    }
+
+    #[test]
+    fn test_mime_types() {
+        assert_eq!(mime_type_for_filetype("rust"), "text/x-rust");
+        assert_eq!(mime_type_for_filetype(""), "text/plain");
+        assert_eq!(
+            mime_type_for_path(std::path::Path::new("a/b.yml")),
+            "application/yaml"
+        );
+        assert_eq!(
+            mime_type_for_path(std::path::Path::new("Makefile")),
+            "text/plain"
+        );
     }
 }
@@ -10,12 +10,21 @@ use rmcp::{
 };
 use tracing::instrument;
 
-use super::core::{NeovimMcpServer, find_get_all_targets};
+use super::core::{NeovimMcpServer, TargetInfo, describe_target, find_get_all_targets, spawn_nvim};
 use super::lua_tools;
-use crate::neovim::client::TypeHierarchyItem;
+use super::output::{
+    Broadcast, ConnectionResult, EditsOrMessage, LspClientOutput, ToolOutput, output_schema,
+    structured_result,
+};
+use crate::neovim::client::{
+    BufferInfo, CallHierarchyIncomingCall, CallHierarchyOutgoingCall, Diagnostic,
+    DocumentSymbolResult, HoverResult, LocateResult, Location, LspClient, NavigateResult, TextEdit,
+    TypeHierarchyItem, describe_text_edits,
+};
 use crate::neovim::{
-    CallHierarchyItem, CodeAction, DocumentIdentifier, FormattingOptions, NeovimClient, Position,
-    PrepareRenameResult, Range, WorkspaceEdit, string_or_struct,
+    CallHierarchyItem, CodeAction, DocumentIdentifier, FormattingOptions, NeovimClient,
+    NeovimClientConfigOverrides, NeovimClientTrait, NeovimError, Position, PrepareRenameResult,
+    Range, SymbolLocator, WorkspaceEdit, option_string_or_struct, string_or_struct,
 };
 
 /// Connect to Neovim instance via unix socket or TCP
@@ -23,20 +32,60 @@ use crate::neovim::{
 pub struct ConnectNvimRequest {
     /// target can be a unix socket path or a TCP address
     pub target: String,
+    /// Client settings for this connection, defaulting to the server configuration
+    #[serde(flatten)]
+    pub config: NeovimClientConfigOverrides,
+}
+
+/// Start a headless Neovim instance
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct SpawnNvimRequest {
+    /// Working directory of the instance, usually the project root (optional, defaults to the server's working directory)
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Init file to load instead of the user's configuration (optional, "NONE" skips all configuration)
+    #[serde(default)]
+    pub init_file: Option<String>,
+    /// Talk to the instance over its stdin/stdout instead of a socket file (optional, the instance cannot be reconnected once it exits)
+    #[serde(default)]
+    pub embed: bool,
+    /// Client settings for this connection, defaulting to the server configuration
+    #[serde(flatten)]
+    pub config: NeovimClientConfigOverrides,
 }
 
 /// New parameter struct for connection-aware requests
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct ConnectionRequest {
-    /// Unique identifier for the target Neovim instance
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
+}
+
+/// Default connection parameters
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct SetDefaultConnectionRequest {
+    /// Connection to use when `connection_id` is omitted
     pub connection_id: String,
 }
 
+/// List buffers parameters
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListBuffersRequest {
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
+    /// Run on every connection concurrently and return results keyed by connection ID
+    #[serde(default)]
+    pub all_connections: bool,
+}
+
 /// Updated parameter struct for buffer operations
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct BufferRequest {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
     /// Neovim Buffer ID
     pub id: u64,
 }
@@ -44,8 +93,9 @@ pub struct BufferRequest {
 /// Buffer read request parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct BufferReadRequest {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
     /// Universal document identifier
     // Supports both string and struct deserialization.
     // Compatible with Claude Code when using subscription.
@@ -66,8 +116,9 @@ fn default_end_line() -> i64 {
 /// Lua execution request
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct ExecuteLuaRequest {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
     /// Lua code to execute in Neovim
     pub code: String,
 }
@@ -75,8 +126,9 @@ pub struct ExecuteLuaRequest {
 /// Wait for LSP client readiness parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct WaitForLspReadyRequest {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
     /// Optional specific LSP client name to wait for (waits for any client if None)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub client_name: Option<String>,
@@ -89,29 +141,85 @@ fn default_timeout() -> u64 {
     5000
 }
 
+/// Target of a position-based request: either a zero-based `line`/`character`
+/// position, or a `symbol` locator resolved against the document
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, schemars::JsonSchema)]
+#[serde(try_from = "PositionOrSymbolFields")]
+#[schemars(with = "PositionOrSymbolFields")]
+pub enum PositionOrSymbol {
+    Position(Position),
+    Symbol(SymbolLocator),
+}
+
+/// Flattened fields of [`PositionOrSymbol`] as they appear in tool parameters
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct PositionOrSymbolFields {
+    /// Zero-based line, required with `character` unless `symbol` is given
+    #[serde(default)]
+    line: Option<u64>,
+    /// Zero-based character offset, required with `line` unless `symbol` is given
+    #[serde(default)]
+    character: Option<u64>,
+    /// Alternative to line/character: a symbol path such as "Server::connect", or
+    /// {"line": <zero-based line>, "text_match": <text occurring once on that line>}
+    // Supports both string and struct deserialization.
+    #[serde(default, deserialize_with = "option_string_or_struct")]
+    symbol: Option<SymbolLocator>,
+}
+
+impl TryFrom<PositionOrSymbolFields> for PositionOrSymbol {
+    type Error = String;
+
+    fn try_from(fields: PositionOrSymbolFields) -> Result<Self, Self::Error> {
+        let position = match (fields.line, fields.character) {
+            (Some(line), Some(character)) => Some(Position { line, character }),
+            (None, None) => None,
+            (Some(_), None) => return Err("Incomplete position: `character` is missing".into()),
+            (None, Some(_)) => return Err("Incomplete position: `line` is missing".into()),
+        };
+        match (position, fields.symbol) {
+            (Some(position), None) => Ok(Self::Position(position)),
+            (None, Some(symbol)) => Ok(Self::Symbol(symbol)),
+            (Some(_), Some(_)) => Err(
+                "Provide either a position (line/character) or a symbol locator, not both".into(),
+            ),
+            (None, None) => {
+                Err("Missing position: provide line/character or a symbol locator".into())
+            }
+        }
+    }
+}
+
 /// Workspace symbols parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct WorkspaceSymbolsParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
-    /// Lsp client name
-    pub lsp_client_name: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
+    /// Lsp client name (optional, selected automatically when omitted)
+    #[serde(default)]
+    pub lsp_client_name: Option<String>,
     /// A query string to filter symbols by. Clients may send an empty string here to request all symbols.
     pub query: String,
+    /// Run on every connection concurrently and return results keyed by connection ID
+    #[serde(default)]
+    pub all_connections: bool,
 }
 
 /// Code Actions parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct CodeActionsParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
     /// Universal document identifier
     // Supports both string and struct deserialization.
     // Compatible with Claude Code when using subscription.
     #[serde(deserialize_with = "string_or_struct")]
     pub document: DocumentIdentifier,
-    /// Lsp client name
-    pub lsp_client_name: String,
+    /// Lsp client name (optional, selected automatically when omitted)
+    #[serde(default)]
+    pub lsp_client_name: Option<String>,
     /// Range start position, line number starts from 0
     pub start_line: u64,
     /// Range start position, character number starts from 0
@@ -125,49 +233,55 @@ pub struct CodeActionsParams {
 /// Hover parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct HoverParam {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
     /// Universal document identifier
     // Supports both string and struct deserialization.
     // Compatible with Claude Code when using subscription.
     #[serde(deserialize_with = "string_or_struct")]
     pub document: DocumentIdentifier,
-    /// Lsp client name
-    pub lsp_client_name: String,
-    /// Symbol position (zero-based)
+    /// Lsp client name (optional, selected automatically when omitted)
+    #[serde(default)]
+    pub lsp_client_name: Option<String>,
+    /// Symbol position or locator
     #[serde(flatten)]
-    pub position: Position,
+    pub target: PositionOrSymbol,
 }
 
 /// Document symbols parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct DocumentSymbolsParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
     /// Universal document identifier
     // Supports both string and struct deserialization.
     // Compatible with Claude Code when using subscription.
     #[serde(deserialize_with = "string_or_struct")]
     pub document: DocumentIdentifier,
-    /// Lsp client name
-    pub lsp_client_name: String,
+    /// Lsp client name (optional, selected automatically when omitted)
+    #[serde(default)]
+    pub lsp_client_name: Option<String>,
 }
 
 /// References parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct ReferencesParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
     /// Universal document identifier
     // Supports both string and struct deserialization.
     // Compatible with Claude Code when using subscription.
     #[serde(deserialize_with = "string_or_struct")]
     pub document: DocumentIdentifier,
-    /// Lsp client name
-    pub lsp_client_name: String,
-    /// Symbol position (zero-based)
+    /// Lsp client name (optional, selected automatically when omitted)
+    #[serde(default)]
+    pub lsp_client_name: Option<String>,
+    /// Symbol position or locator
     #[serde(flatten)]
-    pub position: Position,
+    pub target: PositionOrSymbol,
     /// Include the declaration of the current symbol in the results
     pub include_declaration: bool,
 }
@@ -175,77 +289,86 @@ pub struct ReferencesParams {
 /// Definition parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct DefinitionParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
     /// Universal document identifier
     // Supports both string and struct deserialization.
     // Compatible with Claude Code when using subscription.
     #[serde(deserialize_with = "string_or_struct")]
     pub document: DocumentIdentifier,
-    /// Lsp client name
-    pub lsp_client_name: String,
-    /// Symbol position (zero-based)
+    /// Lsp client name (optional, selected automatically when omitted)
+    #[serde(default)]
+    pub lsp_client_name: Option<String>,
+    /// Symbol position or locator
     #[serde(flatten)]
-    pub position: Position,
+    pub target: PositionOrSymbol,
 }
 
 /// Type definition parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct TypeDefinitionParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
     /// Universal document identifier
     // Supports both string and struct deserialization.
     // Compatible with Claude Code when using subscription.
     #[serde(deserialize_with = "string_or_struct")]
     pub document: DocumentIdentifier,
-    /// Lsp client name
-    pub lsp_client_name: String,
-    /// Symbol position (zero-based)
+    /// Lsp client name (optional, selected automatically when omitted)
+    #[serde(default)]
+    pub lsp_client_name: Option<String>,
+    /// Symbol position or locator
     #[serde(flatten)]
-    pub position: Position,
+    pub target: PositionOrSymbol,
 }
 
 /// Implementation parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct ImplementationParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
     /// Universal document identifier
     // Supports both string and struct deserialization.
     // Compatible with Claude Code when using subscription.
     #[serde(deserialize_with = "string_or_struct")]
     pub document: DocumentIdentifier,
-    /// Lsp client name
-    pub lsp_client_name: String,
-    /// Symbol position (zero-based)
+    /// Lsp client name (optional, selected automatically when omitted)
+    #[serde(default)]
+    pub lsp_client_name: Option<String>,
+    /// Symbol position or locator
     #[serde(flatten)]
-    pub position: Position,
+    pub target: PositionOrSymbol,
 }
 
 /// Declaration parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct DeclarationParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
     /// Universal document identifier
     // Supports both string and struct deserialization.
     // Compatible with Claude Code when using subscription.
     #[serde(deserialize_with = "string_or_struct")]
     pub document: DocumentIdentifier,
-    /// Lsp client name
-    pub lsp_client_name: String,
-    /// Symbol position (zero-based)
+    /// Lsp client name (optional, selected automatically when omitted)
+    #[serde(default)]
+    pub lsp_client_name: Option<String>,
+    /// Symbol position or locator
     #[serde(flatten)]
-    pub position: Position,
+    pub target: PositionOrSymbol,
 }
 
 /// Code action resolve parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct ResolveCodeActionParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
-    /// Lsp client name
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
+    /// Lsp client name, the client that returned the code action
     pub lsp_client_name: String,
     /// Code action to resolve
     // Supports both string and struct deserialization.
@@ -257,9 +380,10 @@ pub struct ResolveCodeActionParams {
 /// Apply workspace edit parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct ApplyWorkspaceEditParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
-    /// Lsp client name
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
+    /// Lsp client name, the client that returned the workspace edit
     pub lsp_client_name: String,
     /// Workspace edit to apply
     // Supports both string and struct deserialization.
@@ -271,18 +395,20 @@ pub struct ApplyWorkspaceEditParams {
 /// Rename parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct RenameParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
     /// Universal document identifier
     // Supports both string and struct deserialization.
     // Compatible with Claude Code when using subscription.
     #[serde(deserialize_with = "string_or_struct")]
     pub document: DocumentIdentifier,
-    /// Lsp client name
-    pub lsp_client_name: String,
-    /// Symbol position (zero-based)
+    /// Lsp client name (optional, selected automatically when omitted)
+    #[serde(default)]
+    pub lsp_client_name: Option<String>,
+    /// Symbol position or locator
     #[serde(flatten)]
-    pub position: Position,
+    pub target: PositionOrSymbol,
     /// The new name of the symbol
     pub new_name: String,
     /// Whether to run prepare rename first to validate the position (default: true)
@@ -297,15 +423,17 @@ fn default_prepare_first() -> bool {
 /// Document formatting parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct DocumentFormattingParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
     /// Universal document identifier
     // Supports both string and struct deserialization.
     // Compatible with Claude Code when using subscription.
     #[serde(deserialize_with = "string_or_struct")]
     pub document: DocumentIdentifier,
-    /// Lsp client name
-    pub lsp_client_name: String,
+    /// Lsp client name (optional, selected automatically when omitted)
+    #[serde(default)]
+    pub lsp_client_name: Option<String>,
     /// The formatting options
     pub options: FormattingOptions,
     /// Whether to apply the text edits automatically (default: false)
@@ -316,15 +444,17 @@ pub struct DocumentFormattingParams {
 /// Document range formatting parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct DocumentRangeFormattingParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
     /// Universal document identifier
     // Supports both string and struct deserialization.
     // Compatible with Claude Code when using subscription.
     #[serde(deserialize_with = "string_or_struct")]
     pub document: DocumentIdentifier,
-    /// Lsp client name
-    pub lsp_client_name: String,
+    /// Lsp client name (optional, selected automatically when omitted)
+    #[serde(default)]
+    pub lsp_client_name: Option<String>,
     /// Range start position, line number starts from 0
     pub start_line: u64,
     /// Range start position, character number starts from 0
@@ -344,15 +474,17 @@ pub struct DocumentRangeFormattingParams {
 /// Organize imports parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct LspOrganizeImportsParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
     /// Universal document identifier
     // Supports both string and struct deserialization.
     // Compatible with Claude Code when using subscription.
     #[serde(deserialize_with = "string_or_struct")]
     pub document: DocumentIdentifier,
-    /// Lsp client name
-    pub lsp_client_name: String,
+    /// Lsp client name (optional, selected automatically when omitted)
+    #[serde(default)]
+    pub lsp_client_name: Option<String>,
     /// Whether to apply the text edits automatically (default: true)
     #[serde(default = "default_true")]
     pub apply_edits: bool,
@@ -365,41 +497,45 @@ fn default_true() -> bool {
 /// Navigate to a specific position in the current buffer or open a file at a specific position
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct NavigateParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
     /// Document to navigate to
     // Supports both string and struct deserialization.
     // Compatible with Claude Code when using subscription.
     #[serde(deserialize_with = "string_or_struct")]
     pub document: DocumentIdentifier,
-    /// Symbol position (zero-based)
+    /// Target position or symbol locator
     #[serde(flatten)]
-    pub position: Position,
+    pub target: PositionOrSymbol,
 }
 
 /// Call hierarchy prepare parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct CallHierarchyPrepareParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
     /// Universal document identifier
     // Supports both string and struct deserialization.
     // Compatible with Claude Code when using subscription.
     #[serde(deserialize_with = "string_or_struct")]
     pub document: DocumentIdentifier,
-    /// Lsp client name
-    pub lsp_client_name: String,
-    /// Symbol position (zero-based)
+    /// Lsp client name (optional, selected automatically when omitted)
+    #[serde(default)]
+    pub lsp_client_name: Option<String>,
+    /// Symbol position or locator
     #[serde(flatten)]
-    pub position: Position,
+    pub target: PositionOrSymbol,
 }
 
 /// Call hierarchy incoming calls parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct CallHierarchyIncomingCallsParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
-    /// Lsp client name
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
+    /// Lsp client name, the client that returned the item
     pub lsp_client_name: String,
     /// Call hierarchy item to get incoming calls for
     #[serde(deserialize_with = "string_or_struct")]
@@ -409,9 +545,10 @@ pub struct CallHierarchyIncomingCallsParams {
 /// Call hierarchy outgoing calls parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct CallHierarchyOutgoingCallsParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
-    /// Lsp client name
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
+    /// Lsp client name, the client that returned the item
     pub lsp_client_name: String,
     /// Call hierarchy item to get outgoing calls for
     #[serde(deserialize_with = "string_or_struct")]
@@ -421,26 +558,29 @@ pub struct CallHierarchyOutgoingCallsParams {
 /// Type Hierarchy Prepare parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct TypeHierarchyPrepareParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
     /// Universal document identifier
     // Supports both string and struct deserialization.
     // Compatible with Claude Code when using subscription.
     #[serde(deserialize_with = "string_or_struct")]
     pub document: DocumentIdentifier,
-    /// Lsp client name
-    pub lsp_client_name: String,
-    /// Symbol position (zero-based)
+    /// Lsp client name (optional, selected automatically when omitted)
+    #[serde(default)]
+    pub lsp_client_name: Option<String>,
+    /// Symbol position or locator
     #[serde(flatten)]
-    pub position: Position,
+    pub target: PositionOrSymbol,
 }
 
 /// Type hierarchy supertypes parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct TypeHierarchySupertypesParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
-    /// Lsp client name
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
+    /// Lsp client name, the client that returned the item
     pub lsp_client_name: String,
     /// Type hierarchy item to get supertypes for
     #[serde(deserialize_with = "string_or_struct")]
@@ -450,15 +590,61 @@ pub struct TypeHierarchySupertypesParams {
 /// Type hierarchy subtypes parameters
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct TypeHierarchySubtypesParams {
-    /// Unique identifier for the target Neovim instance
-    pub connection_id: String,
-    /// Lsp client name
+    /// Unique identifier for the target Neovim instance (optional, uses the default connection when omitted)
+    #[serde(default)]
+    pub connection_id: Option<String>,
+    /// Lsp client name, the client that returned the item
     pub lsp_client_name: String,
     /// Type hierarchy item to get subtypes for
     #[serde(deserialize_with = "string_or_struct")]
     pub item: TypeHierarchyItem,
 }
 
+/// Output of connect and connect_tcp
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ConnectOutput {
+    /// ID of the new connection
+    pub connection_id: String,
+}
+
+/// Output of spawn_nvim
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct SpawnNvimOutput {
+    /// ID of the new connection
+    pub connection_id: String,
+    /// Address of the spawned instance
+    pub target: String,
+    /// Process ID of the spawned instance
+    pub pid: u32,
+}
+
+/// Output of disconnect and set_default_connection
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ConnectionTargetOutput {
+    pub connection_id: String,
+    /// Address of the connected instance
+    pub target: String,
+}
+
+/// Output of wait_for_lsp_ready
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct LspReadyOutput {
+    pub message: String,
+    /// The awaited LSP client, `any` when none was named
+    pub client_name: String,
+    pub timeout_ms: u64,
+}
+
+/// Output of cursor_position, with zero-based row and column
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct CursorPosition {
+    pub buffer_name: String,
+    pub buffer_id: u64,
+    pub window_id: u64,
+    pub row: u64,
+    pub col: u64,
+}
+
 macro_rules! include_files {
     ($($key:ident),* $(,)?) => {{
         let mut map = HashMap::new();
@@ -488,12 +674,221 @@ impl NeovimMcpServer {
     }
 }
 
+/// Resolve the LSP clients to query: the named client, or every client that
+/// supports `method`, preferring those attached to `document`.
+async fn resolve_lsp_clients(
+    client: &(dyn NeovimClientTrait + Send),
+    lsp_client_name: Option<String>,
+    method: &str,
+    document: Option<&DocumentIdentifier>,
+) -> Result<Vec<String>, McpError> {
+    if let Some(name) = lsp_client_name {
+        return Ok(vec![name]);
+    }
+    let names = client.lsp_select_clients(method, document).await?;
+    if names.is_empty() {
+        return Err(McpError::invalid_request(
+            format!("No LSP client supporting {method} found, use lsp_clients to inspect clients"),
+            None,
+        ));
+    }
+    Ok(names)
+}
+
+/// Run a request against every connection concurrently and evaluate to the
+/// results, or the error of each failed connection, keyed by connection ID.
+macro_rules! broadcast {
+    ($server:expr, |$client:ident| $request:expr) => {{
+        let server = $server;
+        let connection_ids: Vec<String> = server
+            .nvim_clients
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect();
+        if connection_ids.is_empty() {
+            return Err(McpError::invalid_request(
+                "No Neovim connection, use get_targets and connect first".to_string(),
+                None,
+            ));
+        }
+        let results =
+            futures::future::join_all(connection_ids.into_iter().map(|connection_id| async move {
+                let result = match server.get_connection(Some(&connection_id)) {
+                    Ok($client) => $request.await.map_err(McpError::from),
+                    Err(e) => Err(e),
+                };
+                (connection_id, result)
+            }))
+            .await;
+        let mut by_connection = std::collections::BTreeMap::new();
+        for (connection_id, result) in results {
+            let result = match result {
+                Ok(value) => ConnectionResult::Ok(value),
+                Err(e) => ConnectionResult::Err {
+                    error: e.message.to_string(),
+                },
+            };
+            by_connection.insert(connection_id, result);
+        }
+        Broadcast::AllConnections(by_connection)
+    }};
+}
+
+/// Query candidate clients in order and evaluate to the name of the client and
+/// its result for the first non-empty result, falling back to the next client
+/// on errors or empty responses.
+macro_rules! first_lsp_result {
+    ($client_names:expr, $is_empty:expr, |$name:ident| $request:expr) => {{
+        let mut found = None;
+        let mut last_err: Option<NeovimError> = None;
+        for $name in $client_names.iter().map(String::as_str) {
+            match $request.await {
+                Ok(result) if !($is_empty)(&result) => {
+                    found = Some(($name.to_string(), result));
+                    last_err = None;
+                    break;
+                }
+                Ok(result) => {
+                    found.get_or_insert(($name.to_string(), result));
+                }
+                Err(e) => {
+                    tracing::debug!("LSP client {} failed: {}", $name, e);
+                    last_err = Some(e);
+                }
+            }
+        }
+        match (found, last_err) {
+            (Some(result), _) => Ok(result),
+            (None, Some(e)) => Err(McpError::from(e)),
+            (None, None) => Err(McpError::invalid_request(
+                "No LSP client to query".to_string(),
+                None,
+            )),
+        }
+    }};
+}
+
+/// Query every candidate client and merge their results, dropping duplicates.
+/// Fails only when all clients fail.
+macro_rules! merge_lsp_results {
+    ($client_names:expr, |$name:ident| $request:expr) => {{
+        let mut merged = Vec::new();
+        let mut last_err: Option<NeovimError> = None;
+        let mut succeeded = false;
+        for $name in $client_names.iter().map(String::as_str) {
+            match $request.await {
+                Ok(results) => {
+                    succeeded = true;
+                    for item in results {
+                        if !merged.contains(&item) {
+                            merged.push(item);
+                        }
+                    }
+                }
+                Err(e) => {
+                    tracing::debug!("LSP client {} failed: {}", $name, e);
+                    last_err = Some(e);
+                }
+            }
+        }
+        match last_err {
+            Some(e) if !succeeded => Err(McpError::from(e)),
+            _ => Ok(merged),
+        }
+    }};
+}
+
+/// Resolve the target position of a request from either an explicit position
+/// or a symbol locator.
+async fn resolve_position(
+    client: &(dyn NeovimClientTrait + Send),
+    lsp_client_name: Option<&str>,
+    document: &DocumentIdentifier,
+    target: PositionOrSymbol,
+) -> Result<Position, McpError> {
+    match target {
+        PositionOrSymbol::Position(position) => Ok(position),
+        PositionOrSymbol::Symbol(SymbolLocator::TextMatch { line, text_match }) => {
+            if text_match.is_empty() {
+                return Err(McpError::invalid_request(
+                    "text_match must not be empty".to_string(),
+                    None,
+                ));
+            }
+            let content = client.read_document(document.clone(), 0, -1).await?;
+            let line_count = content.lines().count();
+            let text = content.lines().nth(line as usize).ok_or_else(|| {
+                McpError::invalid_request(
+                    format!("Line {line} is out of range, the document has {line_count} lines"),
+                    None,
+                )
+            })?;
+            let offsets: Vec<usize> = text.match_indices(&text_match).map(|(i, _)| i).collect();
+            match offsets.as_slice() {
+                [] => Err(McpError::invalid_request(
+                    format!("Text `{text_match}` not found on line {line}: `{text}`"),
+                    None,
+                )),
+                [offset] => Ok(Position {
+                    line,
+                    character: text[..*offset].chars().count() as u64,
+                }),
+                _ => Err(McpError::invalid_request(
+                    format!(
+                        "Text `{text_match}` occurs {} times on line {line}, use a longer text_match",
+                        offsets.len()
+                    ),
+                    None,
+                )),
+            }
+        }
+        PositionOrSymbol::Symbol(SymbolLocator::Path(path)) => {
+            let client_names = resolve_lsp_clients(
+                client,
+                lsp_client_name.map(str::to_string),
+                "textDocument/documentSymbol",
+                Some(document),
+            )
+            .await?;
+            let (_, symbols) = first_lsp_result!(client_names, Option::is_none, |name| client
+                .lsp_document_symbols(name, document.clone()))?;
+            let mut matches = symbols
+                .map(|symbols| symbols.find_symbol(&path))
+                .unwrap_or_default();
+            matches.dedup_by(|a, b| a.1 == b.1);
+            match matches.len() {
+                0 => Err(McpError::invalid_request(
+                    format!("Symbol `{path}` not found in document symbols"),
+                    None,
+                )),
+                1 => Ok(matches.remove(0).1),
+                _ => {
+                    let candidates: Vec<String> = matches
+                        .iter()
+                        .map(|(name, position)| format!("{name} (line {})", position.line))
+                        .collect();
+                    Err(McpError::invalid_request(
+                        format!(
+                            "Symbol `{path}` is ambiguous, candidates: {}; use a longer path or a position",
+                            candidates.join(", ")
+                        ),
+                        None,
+                    ))
+                }
+            }
+        }
+    }
+}
+
 #[tool_router]
 impl NeovimMcpServer {
-    #[tool]
+    #[tool(
+        output_schema = output_schema::<Vec<TargetInfo>>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn get_targets(&self) -> Result<CallToolResult, McpError> {
-        let targets = find_get_all_targets();
+        let targets = find_get_all_targets().await;
         if targets.is_empty() {
             return Err(McpError::invalid_request(
                 "No Neovim targets found".to_string(),
@@ -501,14 +896,28 @@ impl NeovimMcpServer {
             ));
         }
 
-        Ok(CallToolResult::success(vec![Content::json(targets)?]))
+        let targets =
+            futures::future::join_all(targets.iter().map(|target| describe_target(self, target)))
+                .await;
+        structured_result(targets)
     }
 
-    #[tool]
+    #[tool(
+        output_schema = output_schema::<ConnectOutput>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
     #[instrument(skip(self))]
     pub async fn connect(
         &self,
-        Parameters(ConnectNvimRequest { target: path }): Parameters<ConnectNvimRequest>,
+        Parameters(ConnectNvimRequest {
+            target: path,
+            config,
+        }): Parameters<ConnectNvimRequest>,
         ctx: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
         let connection_id = self.generate_shorter_connection_id(&path);
@@ -518,24 +927,33 @@ impl NeovimMcpServer {
             let _ = old_client.disconnect().await;
         }
 
-        let mut client = NeovimClient::default();
+        let mut client =
+            NeovimClient::default().with_config(self.client_config.with_overrides(&config));
         client.connect_path(&path).await?;
 
         self.setup_new_client(&connection_id, Box::new(client), &ctx)
             .await?;
 
-        Ok(CallToolResult::success(vec![Content::json(
-            serde_json::json!({
-                "connection_id": connection_id,
-            }),
-        )?]))
+        structured_result(ConnectOutput { connection_id })
     }
 
-    #[tool(description = "Connect via TCP address")]
+    #[tool(
+        description = "Connect via TCP address",
+        output_schema = output_schema::<ConnectOutput>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
     #[instrument(skip(self))]
     pub async fn connect_tcp(
         &self,
-        Parameters(ConnectNvimRequest { target: address }): Parameters<ConnectNvimRequest>,
+        Parameters(ConnectNvimRequest {
+            target: address,
+            config,
+        }): Parameters<ConnectNvimRequest>,
         ctx: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
         let connection_id = self.generate_shorter_connection_id(&address);
@@ -545,45 +963,100 @@ impl NeovimMcpServer {
             let _ = old_client.disconnect().await;
         }
 
-        let mut client = NeovimClient::default();
+        let mut client =
+            NeovimClient::default().with_config(self.client_config.with_overrides(&config));
         client.connect_tcp(&address).await?;
 
         self.setup_new_client(&connection_id, Box::new(client), &ctx)
             .await?;
 
-        Ok(CallToolResult::success(vec![Content::json(
-            serde_json::json!({
-                "connection_id": connection_id,
-            }),
-        )?]))
+        structured_result(ConnectOutput { connection_id })
     }
 
-    #[tool(description = "Disconnect from Neovim instance")]
+    #[tool(
+        description = "Start a headless Neovim instance and connect to it, it is stopped on disconnect",
+        output_schema = output_schema::<SpawnNvimOutput>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
+    #[instrument(skip(self))]
+    pub async fn spawn_nvim(
+        &self,
+        Parameters(SpawnNvimRequest {
+            cwd,
+            init_file,
+            embed,
+            config,
+        }): Parameters<SpawnNvimRequest>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let (connection_id, client, pid) = spawn_nvim(
+            self,
+            cwd.as_deref(),
+            init_file.as_deref(),
+            embed,
+            self.client_config.with_overrides(&config),
+        )
+        .await?;
+        let target = client.target().unwrap_or_default();
+
+        self.setup_new_client(&connection_id, client, &ctx).await?;
+
+        structured_result(SpawnNvimOutput {
+            connection_id,
+            target,
+            pid,
+        })
+    }
+
+    #[tool(
+        description = "Disconnect from Neovim instance",
+        output_schema = output_schema::<ConnectionTargetOutput>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
     #[instrument(skip(self))]
     pub async fn disconnect(
         &self,
         Parameters(ConnectionRequest { connection_id }): Parameters<ConnectionRequest>,
     ) -> Result<CallToolResult, McpError> {
         // Verify connection exists first
+        let connection_id = self.resolve_connection_id(connection_id.as_deref())?;
         let target = {
-            let client = self.get_connection(&connection_id)?;
+            let client = self.get_connection(Some(&connection_id))?;
             client.target().unwrap_or_else(|| "Unknown".to_string())
         };
+        if self.default_connection_id().as_ref() == Some(&connection_id) {
+            self.set_default_connection_id(None);
+        }
 
         // Remove the connection from the map
         if let Some((_, mut client)) = self.nvim_clients.remove(&connection_id) {
+            self.auto_connected.remove(&connection_id);
+            self.unregister_dynamic_tools(&connection_id);
+            if !client.is_stale()
+                && let Err(e) = client.teardown_autocmd().await
+            {
+                tracing::warn!("Failed to remove autocmd of '{connection_id}': {e}");
+            }
             if let Err(e) = client.disconnect().await {
                 return Err(McpError::internal_error(
                     format!("Failed to disconnect: {e}"),
                     None,
                 ));
             }
-            Ok(CallToolResult::success(vec![Content::json(
-                serde_json::json!({
-                    "connection_id": connection_id,
-                    "target": target,
-                }),
-            )?]))
+            structured_result(ConnectionTargetOutput {
+                connection_id,
+                target,
+            })
         } else {
             Err(McpError::invalid_request(
                 format!("No Neovim connection found for ID: {connection_id}"),
@@ -592,18 +1065,66 @@ impl NeovimMcpServer {
         }
     }
 
-    #[tool(description = "List all open buffers")]
+    #[tool(
+        description = "Set the connection used when connection_id is omitted",
+        output_schema = output_schema::<ConnectionTargetOutput>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    #[instrument(skip(self))]
+    pub async fn set_default_connection(
+        &self,
+        Parameters(SetDefaultConnectionRequest { connection_id }): Parameters<
+            SetDefaultConnectionRequest,
+        >,
+    ) -> Result<CallToolResult, McpError> {
+        let target = {
+            let client = self.get_connection(Some(&connection_id))?;
+            client.target().unwrap_or_else(|| "Unknown".to_string())
+        };
+        self.set_default_connection_id(Some(connection_id.clone()));
+        structured_result(ConnectionTargetOutput {
+            connection_id,
+            target,
+        })
+    }
+
+    #[tool(
+        description = "List all open buffers",
+        output_schema = output_schema::<Broadcast<Vec<BufferInfo>>>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn list_buffers(
         &self,
-        Parameters(ConnectionRequest { connection_id }): Parameters<ConnectionRequest>,
+        Parameters(ListBuffersRequest {
+            connection_id,
+            all_connections,
+        }): Parameters<ListBuffersRequest>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
+        if all_connections {
+            let buffers = broadcast!(self, |client| client.get_buffers());
+            return structured_result(buffers);
+        }
+        let client = self.get_connection(connection_id.as_deref())?;
         let buffers = client.get_buffers().await?;
-        Ok(CallToolResult::success(vec![Content::json(buffers)?]))
+        structured_result(Broadcast::Single(buffers))
     }
 
-    #[tool(description = "Execute Lua code")]
+    #[tool(
+        description = "Execute Lua code",
+        output_schema = output_schema::<ToolOutput<serde_json::Value>>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false,
+            open_world_hint = true
+        )
+    )]
     #[instrument(skip(self))]
     pub async fn exec_lua(
         &self,
@@ -611,18 +1132,25 @@ impl NeovimMcpServer {
             connection_id,
             code,
         }): Parameters<ExecuteLuaRequest>,
+        ctx: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
+        let client = self.get_connection(connection_id.as_deref())?;
+        self.confirm_tool_call("exec_lua", &**client, &ctx.peer, &code)
+            .await?;
         let result = client.execute_lua(&code).await?;
         let json_result = lua_tools::convert_nvim_value_to_json(result).map_err(|e| {
             McpError::internal_error(format!("Failed to convert Lua result to JSON: {}", e), None)
         })?;
-        Ok(CallToolResult::success(vec![Content::json(
-            serde_json::json!({ "result": json_result }),
-        )?]))
+        structured_result(ToolOutput {
+            result: json_result,
+        })
     }
 
-    #[tool(description = "Wait for LSP client to be ready and attached")]
+    #[tool(
+        description = "Wait for LSP client to be attached and finished indexing (no pending progress)",
+        output_schema = output_schema::<LspReadyOutput>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn wait_for_lsp_ready(
         &self,
@@ -632,20 +1160,18 @@ impl NeovimMcpServer {
             timeout_ms,
         }): Parameters<WaitForLspReadyRequest>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
+        let client = self.get_connection(connection_id.as_deref())?;
         client
             .wait_for_lsp_ready(client_name.as_deref(), timeout_ms)
             .await?;
-        Ok(CallToolResult::success(vec![Content::json(
-            serde_json::json!({
-                "message": "LSP client ready",
-                "client_name": client_name.unwrap_or_else(|| "any".to_string()),
-                "timeout_ms": timeout_ms
-            }),
-        )?]))
+        structured_result(LspReadyOutput {
+            message: "LSP client ready".to_string(),
+            client_name: client_name.unwrap_or_else(|| "any".to_string()),
+            timeout_ms,
+        })
     }
 
-    #[tool]
+    #[tool(annotations(read_only_hint = true, open_world_hint = false))]
     #[instrument(skip(self))]
     pub async fn read(
         &self,
@@ -656,34 +1182,50 @@ impl NeovimMcpServer {
             end,
         }): Parameters<BufferReadRequest>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
+        let client = self.get_connection(connection_id.as_deref())?;
+        // Without an explicit end, read at most the connection's read window
+        let end = match client.config().read_window_lines {
+            Some(window) if end == -1 && start >= 0 => start.saturating_add(window as i64),
+            _ => end,
+        };
         let text_content = client.read_document(document, start, end).await?;
         Ok(CallToolResult::success(vec![Content::text(text_content)]))
     }
 
-    #[tool]
+    #[tool(
+        output_schema = output_schema::<Vec<Diagnostic>>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn buffer_diagnostics(
         &self,
         Parameters(BufferRequest { connection_id, id }): Parameters<BufferRequest>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
+        let client = self.get_connection(connection_id.as_deref())?;
         let diagnostics = client.get_buffer_diagnostics(id).await?;
-        Ok(CallToolResult::success(vec![Content::json(diagnostics)?]))
+        structured_result(diagnostics)
     }
 
-    #[tool(description = "Get workspace LSP clients")]
+    #[tool(
+        description = "Get workspace LSP clients",
+        output_schema = output_schema::<Vec<LspClient>>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_clients(
         &self,
         Parameters(ConnectionRequest { connection_id }): Parameters<ConnectionRequest>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
+        let client = self.get_connection(connection_id.as_deref())?;
         let lsp_clients = client.lsp_get_clients().await?;
-        Ok(CallToolResult::success(vec![Content::json(lsp_clients)?]))
+        structured_result(lsp_clients)
     }
 
-    #[tool(description = "Search workspace symbols by query")]
+    #[tool(
+        description = "Search workspace symbols by query",
+        output_schema = output_schema::<Broadcast<Option<DocumentSymbolResult>>>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_workspace_symbols(
         &self,
@@ -691,16 +1233,38 @@ impl NeovimMcpServer {
             connection_id,
             lsp_client_name,
             query,
+            all_connections,
         }): Parameters<WorkspaceSymbolsParams>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
-        let symbols = client
-            .lsp_workspace_symbols(&lsp_client_name, &query)
-            .await?;
-        Ok(CallToolResult::success(vec![Content::json(symbols)?]))
+        if all_connections {
+            let (lsp_client_name, query) = (&lsp_client_name, &query);
+            let symbols = broadcast!(self, |client| async {
+                let client_names = resolve_lsp_clients(
+                    &**client,
+                    lsp_client_name.clone(),
+                    "workspace/symbol",
+                    None,
+                )
+                .await?;
+                first_lsp_result!(client_names, Option::is_none, |name| client
+                    .lsp_workspace_symbols(name, query))
+                .map(|(_, symbols)| symbols)
+            });
+            return structured_result(symbols);
+        }
+        let client = self.get_connection(connection_id.as_deref())?;
+        let client_names =
+            resolve_lsp_clients(&**client, lsp_client_name, "workspace/symbol", None).await?;
+        let (_, symbols) = first_lsp_result!(client_names, Option::is_none, |name| client
+            .lsp_workspace_symbols(name, &query))?;
+        structured_result(Broadcast::Single(symbols))
     }
 
-    #[tool(description = "Get LSP code actions")]
+    #[tool(
+        description = "Get LSP code actions",
+        output_schema = output_schema::<LspClientOutput<Vec<CodeAction>>>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_code_actions(
         &self,
@@ -714,7 +1278,7 @@ impl NeovimMcpServer {
             end_character,
         }): Parameters<CodeActionsParams>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
+        let client = self.get_connection(connection_id.as_deref())?;
         let start = Position {
             line: start_line,
             character: start_character,
@@ -725,13 +1289,27 @@ impl NeovimMcpServer {
         };
         let range = Range { start, end };
 
-        let code_actions = client
-            .lsp_get_code_actions(&lsp_client_name, document, range)
-            .await?;
-        Ok(CallToolResult::success(vec![Content::json(code_actions)?]))
+        let client_names = resolve_lsp_clients(
+            &**client,
+            lsp_client_name,
+            "textDocument/codeAction",
+            Some(&document),
+        )
+        .await?;
+        let (lsp_client_name, code_actions) =
+            first_lsp_result!(client_names, Vec::is_empty, |name| client
+                .lsp_get_code_actions(name, document.clone(), range.clone()))?;
+        structured_result(LspClientOutput {
+            lsp_client_name,
+            result: code_actions,
+        })
     }
 
-    #[tool(description = "Get LSP hover information")]
+    #[tool(
+        description = "Get LSP hover information",
+        output_schema = output_schema::<HoverResult>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_hover(
         &self,
@@ -739,17 +1317,32 @@ impl NeovimMcpServer {
             connection_id,
             document,
             lsp_client_name,
-            position,
+            target,
         }): Parameters<HoverParam>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
-        let hover = client
-            .lsp_hover(&lsp_client_name, document, position)
-            .await?;
-        Ok(CallToolResult::success(vec![Content::json(hover)?]))
+        let client = self.get_connection(connection_id.as_deref())?;
+        let position =
+            resolve_position(&**client, lsp_client_name.as_deref(), &document, target).await?;
+        let client_names = resolve_lsp_clients(
+            &**client,
+            lsp_client_name,
+            "textDocument/hover",
+            Some(&document),
+        )
+        .await?;
+        let (_, hover) = first_lsp_result!(client_names, |_| false, |name| client.lsp_hover(
+            name,
+            document.clone(),
+            position.clone()
+        ))?;
+        structured_result(hover)
     }
 
-    #[tool(description = "Get document symbols")]
+    #[tool(
+        description = "Get document symbols",
+        output_schema = output_schema::<Option<DocumentSymbolResult>>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_document_symbols(
         &self,
@@ -759,14 +1352,24 @@ impl NeovimMcpServer {
             lsp_client_name,
         }): Parameters<DocumentSymbolsParams>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
-        let symbols = client
-            .lsp_document_symbols(&lsp_client_name, document)
-            .await?;
-        Ok(CallToolResult::success(vec![Content::json(symbols)?]))
+        let client = self.get_connection(connection_id.as_deref())?;
+        let client_names = resolve_lsp_clients(
+            &**client,
+            lsp_client_name,
+            "textDocument/documentSymbol",
+            Some(&document),
+        )
+        .await?;
+        let (_, symbols) = first_lsp_result!(client_names, Option::is_none, |name| client
+            .lsp_document_symbols(name, document.clone()))?;
+        structured_result(symbols)
     }
 
-    #[tool(description = "Get LSP references")]
+    #[tool(
+        description = "Get LSP references",
+        output_schema = output_schema::<Vec<Location>>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_references(
         &self,
@@ -774,18 +1377,35 @@ impl NeovimMcpServer {
             connection_id,
             document,
             lsp_client_name,
-            position,
+            target,
             include_declaration,
         }): Parameters<ReferencesParams>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
-        let references = client
-            .lsp_references(&lsp_client_name, document, position, include_declaration)
-            .await?;
-        Ok(CallToolResult::success(vec![Content::json(references)?]))
+        let client = self.get_connection(connection_id.as_deref())?;
+        let position =
+            resolve_position(&**client, lsp_client_name.as_deref(), &document, target).await?;
+        let client_names = resolve_lsp_clients(
+            &**client,
+            lsp_client_name,
+            "textDocument/references",
+            Some(&document),
+        )
+        .await?;
+        // References from every capable client are merged
+        let references = merge_lsp_results!(client_names, |name| client.lsp_references(
+            name,
+            document.clone(),
+            position.clone(),
+            include_declaration,
+        ))?;
+        structured_result(references)
     }
 
-    #[tool(description = "Get LSP definition")]
+    #[tool(
+        description = "Get LSP definition",
+        output_schema = output_schema::<Option<LocateResult>>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_definition(
         &self,
@@ -793,17 +1413,29 @@ impl NeovimMcpServer {
             connection_id,
             document,
             lsp_client_name,
-            position,
+            target,
         }): Parameters<DefinitionParams>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
-        let definition = client
-            .lsp_definition(&lsp_client_name, document, position)
-            .await?;
-        Ok(CallToolResult::success(vec![Content::json(definition)?]))
+        let client = self.get_connection(connection_id.as_deref())?;
+        let position =
+            resolve_position(&**client, lsp_client_name.as_deref(), &document, target).await?;
+        let client_names = resolve_lsp_clients(
+            &**client,
+            lsp_client_name,
+            "textDocument/definition",
+            Some(&document),
+        )
+        .await?;
+        let (_, definition) = first_lsp_result!(client_names, Option::is_none, |name| client
+            .lsp_definition(name, document.clone(), position.clone()))?;
+        structured_result(definition)
     }
 
-    #[tool(description = "Get LSP type definition")]
+    #[tool(
+        description = "Get LSP type definition",
+        output_schema = output_schema::<Option<LocateResult>>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_type_definition(
         &self,
@@ -811,19 +1443,29 @@ impl NeovimMcpServer {
             connection_id,
             document,
             lsp_client_name,
-            position,
+            target,
         }): Parameters<TypeDefinitionParams>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
-        let type_definition = client
-            .lsp_type_definition(&lsp_client_name, document, position)
-            .await?;
-        Ok(CallToolResult::success(vec![Content::json(
-            type_definition,
-        )?]))
+        let client = self.get_connection(connection_id.as_deref())?;
+        let position =
+            resolve_position(&**client, lsp_client_name.as_deref(), &document, target).await?;
+        let client_names = resolve_lsp_clients(
+            &**client,
+            lsp_client_name,
+            "textDocument/typeDefinition",
+            Some(&document),
+        )
+        .await?;
+        let (_, type_definition) = first_lsp_result!(client_names, Option::is_none, |name| client
+            .lsp_type_definition(name, document.clone(), position.clone()))?;
+        structured_result(type_definition)
     }
 
-    #[tool(description = "Get LSP implementation")]
+    #[tool(
+        description = "Get LSP implementation",
+        output_schema = output_schema::<Option<LocateResult>>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_implementations(
         &self,
@@ -831,19 +1473,29 @@ impl NeovimMcpServer {
             connection_id,
             document,
             lsp_client_name,
-            position,
+            target,
         }): Parameters<ImplementationParams>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
-        let implementation = client
-            .lsp_implementation(&lsp_client_name, document, position)
-            .await?;
-        Ok(CallToolResult::success(vec![Content::json(
-            implementation,
-        )?]))
+        let client = self.get_connection(connection_id.as_deref())?;
+        let position =
+            resolve_position(&**client, lsp_client_name.as_deref(), &document, target).await?;
+        let client_names = resolve_lsp_clients(
+            &**client,
+            lsp_client_name,
+            "textDocument/implementation",
+            Some(&document),
+        )
+        .await?;
+        let (_, implementation) = first_lsp_result!(client_names, Option::is_none, |name| client
+            .lsp_implementation(name, document.clone(), position.clone()))?;
+        structured_result(implementation)
     }
 
-    #[tool(description = "Get LSP declaration")]
+    #[tool(
+        description = "Get LSP declaration",
+        output_schema = output_schema::<Option<LocateResult>>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_declaration(
         &self,
@@ -851,17 +1503,29 @@ impl NeovimMcpServer {
             connection_id,
             document,
             lsp_client_name,
-            position,
+            target,
         }): Parameters<DeclarationParams>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
-        let declaration = client
-            .lsp_declaration(&lsp_client_name, document, position)
-            .await?;
-        Ok(CallToolResult::success(vec![Content::json(declaration)?]))
+        let client = self.get_connection(connection_id.as_deref())?;
+        let position =
+            resolve_position(&**client, lsp_client_name.as_deref(), &document, target).await?;
+        let client_names = resolve_lsp_clients(
+            &**client,
+            lsp_client_name,
+            "textDocument/declaration",
+            Some(&document),
+        )
+        .await?;
+        let (_, declaration) = first_lsp_result!(client_names, Option::is_none, |name| client
+            .lsp_declaration(name, document.clone(), position.clone()))?;
+        structured_result(declaration)
     }
 
-    #[tool(description = "Resolve a code action that may have incomplete data")]
+    #[tool(
+        description = "Resolve a code action that may have incomplete data",
+        output_schema = output_schema::<CodeAction>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_resolve_code_action(
         &self,
@@ -871,16 +1535,23 @@ impl NeovimMcpServer {
             code_action,
         }): Parameters<ResolveCodeActionParams>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
+        let client = self.get_connection(connection_id.as_deref())?;
         let resolved_action = client
             .lsp_resolve_code_action(&lsp_client_name, code_action)
             .await?;
-        Ok(CallToolResult::success(vec![Content::json(
-            resolved_action,
-        )?]))
+        structured_result(resolved_action)
     }
 
-    #[tool(description = "Apply workspace edits using Neovim's LSP utility functions")]
+    #[tool(
+        description = "Apply workspace edits using Neovim's LSP utility functions",
+        output_schema = output_schema::<String>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_apply_edit(
         &self,
@@ -889,15 +1560,32 @@ impl NeovimMcpServer {
             lsp_client_name,
             workspace_edit,
         }): Parameters<ApplyWorkspaceEditParams>,
+        ctx: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
+        let client = self.get_connection(connection_id.as_deref())?;
+        self.confirm_tool_call(
+            "lsp_apply_edit",
+            &**client,
+            &ctx.peer,
+            &workspace_edit.describe(),
+        )
+        .await?;
         client
             .lsp_apply_workspace_edit(&lsp_client_name, workspace_edit)
             .await?;
-        Ok(CallToolResult::success(vec![Content::text("success")]))
+        structured_result("success".to_string())
     }
 
-    #[tool(description = "Rename symbol across workspace using LSP with optional validation")]
+    #[tool(
+        description = "Rename symbol across workspace using LSP with optional validation",
+        output_schema = output_schema::<String>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_rename(
         &self,
@@ -905,12 +1593,24 @@ impl NeovimMcpServer {
             connection_id,
             document,
             lsp_client_name,
-            position,
+            target,
             new_name,
             prepare_first,
         }): Parameters<RenameParams>,
+        ctx: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
+        let client = self.get_connection(connection_id.as_deref())?;
+        let position =
+            resolve_position(&**client, lsp_client_name.as_deref(), &document, target).await?;
+        // Edits must come from a single client, so use the preferred one
+        let lsp_client_name = resolve_lsp_clients(
+            &**client,
+            lsp_client_name,
+            "textDocument/rename",
+            Some(&document),
+        )
+        .await?
+        .remove(0);
 
         // Optionally run prepare rename first to validate the position
         if prepare_first {
@@ -954,13 +1654,18 @@ impl NeovimMcpServer {
             .await?;
 
         if let Some(edit) = workspace_edit {
+            self.confirm_tool_call(
+                "lsp_rename",
+                &**client,
+                &ctx.peer,
+                &format!("Rename to '{new_name}':\n{}", edit.describe()),
+            )
+            .await?;
             // Apply the workspace edit automatically
             client
                 .lsp_apply_workspace_edit(&lsp_client_name, edit)
                 .await?;
-            Ok(CallToolResult::success(vec![Content::text(
-                "Rename completed successfully",
-            )]))
+            structured_result("Rename completed successfully".to_string())
         } else {
             Err(McpError::invalid_request(
                 "Rename operation is not valid at this position".to_string(),
@@ -969,7 +1674,16 @@ impl NeovimMcpServer {
         }
     }
 
-    #[tool(description = "Format entire document using LSP with optional auto-apply")]
+    #[tool(
+        description = "Format entire document using LSP with optional auto-apply",
+        output_schema = output_schema::<EditsOrMessage<Vec<TextEdit>>>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_formatting(
         &self,
@@ -980,28 +1694,57 @@ impl NeovimMcpServer {
             options,
             apply_edits,
         }): Parameters<DocumentFormattingParams>,
+        ctx: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
+        let client = self.get_connection(connection_id.as_deref())?;
+        // Edits must come from a single client, so use the preferred one
+        let lsp_client_name = resolve_lsp_clients(
+            &**client,
+            lsp_client_name,
+            "textDocument/formatting",
+            Some(&document),
+        )
+        .await?
+        .remove(0);
         let text_edits = client
             .lsp_formatting(&lsp_client_name, document.clone(), options)
             .await?;
 
         if apply_edits {
+            if !text_edits.is_empty() {
+                self.confirm_tool_call(
+                    "lsp_formatting",
+                    &**client,
+                    &ctx.peer,
+                    &format!(
+                        "Apply formatting edits:\n{}",
+                        describe_text_edits(&text_edits)
+                    ),
+                )
+                .await?;
+            }
             // Apply the text edits automatically
             client
                 .lsp_apply_text_edits(&lsp_client_name, document, text_edits)
                 .await?;
-            Ok(CallToolResult::success(vec![Content::text(
-                "Formatting applied successfully",
-            )]))
+            structured_result(EditsOrMessage::<Vec<TextEdit>>::Message(
+                "Formatting applied successfully".to_string(),
+            ))
         } else {
             // Return the text edits for inspection
-            Ok(CallToolResult::success(vec![Content::json(text_edits)?]))
+            structured_result(EditsOrMessage::Edits(text_edits))
         }
     }
 
     #[tool(
-        description = "Format a specific range in a document using LSP with optional auto-apply"
+        description = "Format a specific range in a document using LSP with optional auto-apply",
+        output_schema = output_schema::<EditsOrMessage<Vec<TextEdit>>>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
     )]
     #[instrument(skip(self))]
     pub async fn lsp_range_formatting(
@@ -1017,8 +1760,18 @@ impl NeovimMcpServer {
             options,
             apply_edits,
         }): Parameters<DocumentRangeFormattingParams>,
+        ctx: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
+        let client = self.get_connection(connection_id.as_deref())?;
+        // Edits must come from a single client, so use the preferred one
+        let lsp_client_name = resolve_lsp_clients(
+            &**client,
+            lsp_client_name,
+            "textDocument/rangeFormatting",
+            Some(&document),
+        )
+        .await?
+        .remove(0);
         let start = Position {
             line: start_line,
             character: start_character,
@@ -1034,20 +1787,41 @@ impl NeovimMcpServer {
             .await?;
 
         if apply_edits {
+            if !text_edits.is_empty() {
+                self.confirm_tool_call(
+                    "lsp_range_formatting",
+                    &**client,
+                    &ctx.peer,
+                    &format!(
+                        "Apply formatting edits:\n{}",
+                        describe_text_edits(&text_edits)
+                    ),
+                )
+                .await?;
+            }
             // Apply the text edits automatically
             client
                 .lsp_apply_text_edits(&lsp_client_name, document, text_edits)
                 .await?;
-            Ok(CallToolResult::success(vec![Content::text(
-                "Range formatting applied successfully",
-            )]))
+            structured_result(EditsOrMessage::<Vec<TextEdit>>::Message(
+                "Range formatting applied successfully".to_string(),
+            ))
         } else {
             // Return the text edits for inspection
-            Ok(CallToolResult::success(vec![Content::json(text_edits)?]))
+            structured_result(EditsOrMessage::Edits(text_edits))
         }
     }
 
-    #[tool(description = "Sort and organize imports")]
+    #[tool(
+        description = "Sort and organize imports",
+        output_schema = output_schema::<EditsOrMessage<LspClientOutput<Vec<CodeAction>>>>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_organize_imports(
         &self,
@@ -1057,8 +1831,18 @@ impl NeovimMcpServer {
             lsp_client_name,
             apply_edits,
         }): Parameters<LspOrganizeImportsParams>,
+        ctx: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
+        let client = self.get_connection(connection_id.as_deref())?;
+        // Edits must come from a single client, so use the preferred one
+        let lsp_client_name = resolve_lsp_clients(
+            &**client,
+            lsp_client_name,
+            "textDocument/codeAction",
+            Some(&document),
+        )
+        .await?
+        .remove(0);
 
         // Get organize imports code actions for the entire document
         let code_actions = client
@@ -1066,14 +1850,17 @@ impl NeovimMcpServer {
             .await?;
 
         if code_actions.is_empty() {
-            return Ok(CallToolResult::success(vec![Content::text(
-                "No organize imports actions available for this document",
-            )]));
+            return structured_result(EditsOrMessage::<LspClientOutput<Vec<CodeAction>>>::Message(
+                "No organize imports actions available for this document".to_string(),
+            ));
         }
 
         if !apply_edits {
             // Return the code actions for inspection
-            return Ok(CallToolResult::success(vec![Content::json(code_actions)?]));
+            return structured_result(EditsOrMessage::Edits(LspClientOutput {
+                lsp_client_name,
+                result: code_actions,
+            }));
         }
 
         // Apply the first/preferred organize imports action
@@ -1090,12 +1877,19 @@ impl NeovimMcpServer {
 
         // Apply the workspace edit
         if let Some(edit) = resolved_action.edit() {
+            self.confirm_tool_call(
+                "lsp_organize_imports",
+                &**client,
+                &ctx.peer,
+                &format!("{}:\n{}", resolved_action.title(), edit.describe()),
+            )
+            .await?;
             client
                 .lsp_apply_workspace_edit(&lsp_client_name, edit.clone())
                 .await?;
-            Ok(CallToolResult::success(vec![Content::text(
-                "Imports organized successfully",
-            )]))
+            structured_result(EditsOrMessage::<LspClientOutput<Vec<CodeAction>>>::Message(
+                "Imports organized successfully".to_string(),
+            ))
         } else {
             Err(McpError::invalid_request(
                 "Organize imports action does not contain workspace edit".to_string(),
@@ -1105,30 +1899,42 @@ impl NeovimMcpServer {
     }
 
     #[tool(
-        description = "Get the current cursor position: buffer id, buffer name, window id, and zero-based row/col index"
+        description = "Get the current cursor position: buffer id, buffer name, window id, and zero-based row/col index",
+        output_schema = output_schema::<CursorPosition>(),
+        annotations(read_only_hint = true, open_world_hint = false)
     )]
     #[instrument(skip(self))]
     pub async fn cursor_position(
         &self,
         Parameters(ConnectionRequest { connection_id }): Parameters<ConnectionRequest>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
+        let client = self.get_connection(connection_id.as_deref())?;
         let lua_code = include_str!("./lua/cursor_position.lua");
         let result = client.execute_lua(lua_code).await?;
 
-        // Convert nvim Value to serde_json::Value for serialization
+        // Convert nvim Value to serde_json::Value for deserialization
         let json_result = lua_tools::convert_nvim_value_to_json(result).map_err(|e| {
             McpError::internal_error(
                 format!("Failed to convert cursor position result to JSON: {}", e),
                 None,
             )
         })?;
+        let position: CursorPosition = serde_json::from_value(json_result).map_err(|e| {
+            McpError::internal_error(format!("Invalid cursor position result: {}", e), None)
+        })?;
 
-        Ok(CallToolResult::success(vec![Content::json(json_result)?]))
+        structured_result(position)
     }
 
     #[tool(
-        description = "Navigate to a specific position in the current buffer or open a file at a specific position"
+        description = "Navigate to a specific position in the current buffer or open a file at a specific position",
+        output_schema = output_schema::<NavigateResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
     )]
     #[instrument(skip(self))]
     pub async fn navigate(
@@ -1136,15 +1942,20 @@ impl NeovimMcpServer {
         Parameters(NavigateParams {
             connection_id,
             document,
-            position,
+            target,
         }): Parameters<NavigateParams>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
+        let client = self.get_connection(connection_id.as_deref())?;
+        let position = resolve_position(&**client, None, &document, target).await?;
         let result = client.navigate(document, position).await?;
-        Ok(CallToolResult::success(vec![Content::json(result)?]))
+        structured_result(result)
     }
 
-    #[tool(description = "Prepare call hierarchy for a symbol at a specific position")]
+    #[tool(
+        description = "Prepare call hierarchy for a symbol at a specific position",
+        output_schema = output_schema::<LspClientOutput<Option<Vec<CallHierarchyItem>>>>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_call_hierarchy_prepare(
         &self,
@@ -1152,17 +1963,33 @@ impl NeovimMcpServer {
             connection_id,
             document,
             lsp_client_name,
-            position,
+            target,
         }): Parameters<CallHierarchyPrepareParams>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
-        let result = client
-            .lsp_call_hierarchy_prepare(&lsp_client_name, document, position)
-            .await?;
-        Ok(CallToolResult::success(vec![Content::json(result)?]))
+        let client = self.get_connection(connection_id.as_deref())?;
+        let position =
+            resolve_position(&**client, lsp_client_name.as_deref(), &document, target).await?;
+        let client_names = resolve_lsp_clients(
+            &**client,
+            lsp_client_name,
+            "textDocument/prepareCallHierarchy",
+            Some(&document),
+        )
+        .await?;
+        let (lsp_client_name, result) =
+            first_lsp_result!(client_names, Option::is_none, |name| client
+                .lsp_call_hierarchy_prepare(name, document.clone(), position.clone()))?;
+        structured_result(LspClientOutput {
+            lsp_client_name,
+            result,
+        })
     }
 
-    #[tool(description = "Get incoming calls for a call hierarchy item")]
+    #[tool(
+        description = "Get incoming calls for a call hierarchy item",
+        output_schema = output_schema::<Option<Vec<CallHierarchyIncomingCall>>>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_call_hierarchy_incoming_calls(
         &self,
@@ -1172,14 +1999,18 @@ impl NeovimMcpServer {
             item,
         }): Parameters<CallHierarchyIncomingCallsParams>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
+        let client = self.get_connection(connection_id.as_deref())?;
         let result = client
             .lsp_call_hierarchy_incoming_calls(&lsp_client_name, item)
             .await?;
-        Ok(CallToolResult::success(vec![Content::json(result)?]))
+        structured_result(result)
     }
 
-    #[tool(description = "Get outgoing calls for a call hierarchy item")]
+    #[tool(
+        description = "Get outgoing calls for a call hierarchy item",
+        output_schema = output_schema::<Option<Vec<CallHierarchyOutgoingCall>>>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_call_hierarchy_outgoing_calls(
         &self,
@@ -1189,14 +2020,18 @@ impl NeovimMcpServer {
             item,
         }): Parameters<CallHierarchyOutgoingCallsParams>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
+        let client = self.get_connection(connection_id.as_deref())?;
         let result = client
             .lsp_call_hierarchy_outgoing_calls(&lsp_client_name, item)
             .await?;
-        Ok(CallToolResult::success(vec![Content::json(result)?]))
+        structured_result(result)
     }
 
-    #[tool(description = "Prepare type hierarchy for a symbol at a specific position")]
+    #[tool(
+        description = "Prepare type hierarchy for a symbol at a specific position",
+        output_schema = output_schema::<LspClientOutput<Option<Vec<TypeHierarchyItem>>>>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_type_hierarchy_prepare(
         &self,
@@ -1204,17 +2039,33 @@ impl NeovimMcpServer {
             connection_id,
             document,
             lsp_client_name,
-            position,
+            target,
         }): Parameters<TypeHierarchyPrepareParams>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
-        let result = client
-            .lsp_type_hierarchy_prepare(&lsp_client_name, document, position)
-            .await?;
-        Ok(CallToolResult::success(vec![Content::json(result)?]))
+        let client = self.get_connection(connection_id.as_deref())?;
+        let position =
+            resolve_position(&**client, lsp_client_name.as_deref(), &document, target).await?;
+        let client_names = resolve_lsp_clients(
+            &**client,
+            lsp_client_name,
+            "textDocument/prepareTypeHierarchy",
+            Some(&document),
+        )
+        .await?;
+        let (lsp_client_name, result) =
+            first_lsp_result!(client_names, Option::is_none, |name| client
+                .lsp_type_hierarchy_prepare(name, document.clone(), position.clone()))?;
+        structured_result(LspClientOutput {
+            lsp_client_name,
+            result,
+        })
     }
 
-    #[tool(description = "Get supertypes for a type hierarchy item")]
+    #[tool(
+        description = "Get supertypes for a type hierarchy item",
+        output_schema = output_schema::<Option<Vec<TypeHierarchyItem>>>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_type_hierarchy_supertypes(
         &self,
@@ -1224,14 +2075,18 @@ impl NeovimMcpServer {
             item,
         }): Parameters<TypeHierarchySupertypesParams>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
+        let client = self.get_connection(connection_id.as_deref())?;
         let result = client
             .lsp_type_hierarchy_supertypes(&lsp_client_name, item)
             .await?;
-        Ok(CallToolResult::success(vec![Content::json(result)?]))
+        structured_result(result)
     }
 
-    #[tool(description = "Get subtypes for a type hierarchy item")]
+    #[tool(
+        description = "Get subtypes for a type hierarchy item",
+        output_schema = output_schema::<Option<Vec<TypeHierarchyItem>>>(),
+        annotations(read_only_hint = true, open_world_hint = false)
+    )]
     #[instrument(skip(self))]
     pub async fn lsp_type_hierarchy_subtypes(
         &self,
@@ -1241,11 +2096,11 @@ impl NeovimMcpServer {
             item,
         }): Parameters<TypeHierarchySubtypesParams>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self.get_connection(&connection_id)?;
+        let client = self.get_connection(connection_id.as_deref())?;
         let result = client
             .lsp_type_hierarchy_subtypes(&lsp_client_name, item)
             .await?;
-        Ok(CallToolResult::success(vec![Content::json(result)?]))
+        structured_result(result)
     }
 }
 
@@ -1253,3 +2108,80 @@ impl NeovimMcpServer {
 pub fn build_tool_router() -> ToolRouter<NeovimMcpServer> {
     NeovimMcpServer::tool_router()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_annotations() {
+        let tools = build_tool_router().list_all();
+        assert_eq!(tools.len(), 35);
+        for tool in &tools {
+            let annotations = tool
+                .annotations
+                .as_ref()
+                .unwrap_or_else(|| panic!("{} has no annotations", tool.name));
+            assert!(annotations.read_only_hint.is_some());
+            assert!(annotations.open_world_hint.is_some());
+            if annotations.read_only_hint == Some(false) {
+                assert!(annotations.destructive_hint.is_some());
+                assert!(annotations.idempotent_hint.is_some());
+            }
+        }
+
+        let annotations = |name: &str| {
+            tools
+                .iter()
+                .find(|tool| tool.name == name)
+                .and_then(|tool| tool.annotations.clone())
+                .unwrap()
+        };
+        assert_eq!(annotations("lsp_hover").read_only_hint, Some(true));
+        assert_eq!(annotations("lsp_hover").open_world_hint, Some(false));
+        assert_eq!(annotations("exec_lua").open_world_hint, Some(true));
+        for name in ["exec_lua", "lsp_apply_edit", "lsp_rename"] {
+            assert_eq!(annotations(name).read_only_hint, Some(false));
+            assert_eq!(annotations(name).destructive_hint, Some(true));
+        }
+    }
+
+    #[test]
+    fn test_position_or_symbol_params() {
+        let parse = |value: serde_json::Value| serde_json::from_value::<HoverParam>(value);
+
+        let params = parse(serde_json::json!({
+            "document": {"buffer_id": 0},
+            "line": 3,
+            "character": 7,
+        }))
+        .unwrap();
+        assert_eq!(
+            params.target,
+            PositionOrSymbol::Position(Position {
+                line: 3,
+                character: 7
+            })
+        );
+
+        let params = parse(serde_json::json!({
+            "document": {"buffer_id": 0},
+            "symbol": "Server::connect",
+        }))
+        .unwrap();
+        assert_eq!(
+            params.target,
+            PositionOrSymbol::Symbol(SymbolLocator::Path("Server::connect".to_string()))
+        );
+
+        let err = parse(serde_json::json!({"document": {"buffer_id": 0}, "line": 3})).unwrap_err();
+        assert!(err.to_string().contains("`character` is missing"), "{err}");
+        let err = parse(serde_json::json!({"document": {"buffer_id": 0}})).unwrap_err();
+        assert!(err.to_string().contains("Missing position"), "{err}");
+
+        let schema = serde_json::to_value(schemars::schema_for!(HoverParam)).unwrap();
+        for field in ["line", "character", "symbol"] {
+            assert!(schema["properties"].get(field).is_some(), "{schema}");
+        }
+    }
+}
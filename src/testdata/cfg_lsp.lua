@@ -60,6 +60,11 @@ M.setup({
                 },
                 required = { "buffer_id" },
             },
+            annotations = {
+                read_only = false,
+                destructive = false,
+                idempotent = true,
+            },
             handler = function(params)
                 local buf_id = params.buffer_id
 
@@ -92,4 +97,25 @@ M.setup({
             end,
         },
     },
+    custom_prompts = {
+        summarize_buffer = {
+            description = "Summarize a buffer",
+            arguments = {
+                { name = "focus", description = "What to focus on", required = true },
+            },
+            handler = function(args)
+                return "Summarize the current buffer, focusing on " .. args.focus
+            end,
+        },
+    },
+    custom_resources = {
+        buffer_count = {
+            uri = "test://buffer-count",
+            description = "Number of loaded buffers",
+            mime_type = "application/json",
+            handler = function()
+                return { count = #vim.api.nvim_list_bufs() }
+            end,
+        },
+    },
 })